@@ -1,6 +1,10 @@
 //! Y Combinator
 
-use lamcalc::{lambda, Error};
+use lamcalc::{
+    lambda,
+    numeral::{decode, Encoding},
+    Error,
+};
 
 fn main() -> Result<(), Error> {
     // prepare some nats
@@ -8,7 +12,7 @@ fn main() -> Result<(), Error> {
     let suc = lambda!(n. f. x. f (n f x));
     let prev = lambda!(n. f. x. n (g. h. h (g f)) (u. x) (u. u));
     let mut nats = vec![zero];
-    for i in 1..10 {
+    for i in 1..11 {
         let sx = lambda!({suc} {nats[i - 1]}).simplify(true)?.to_owned();
         nats.push(sx);
         assert_eq!(
@@ -32,21 +36,21 @@ fn main() -> Result<(), Error> {
     let mut y = lambda!(f. (x. f (x x)) (x. f (x x)));
 
     // factorial
-    let mut fact = lambda!(y. n. {if_n_is_zero} n (f. x. f x) ({mul} n (y ({prev} n))));
-
-    eprintln!("simplify fact");
-    while fact.eval_normal_order(true, true) {
-        eprintln!("fact = {}", fact);
-    }
-
+    let fact = lambda!(y. n. {if_n_is_zero} n (f. x. f x) ({mul} n (y ({prev} n))));
     let y_fact = lambda!({y} {fact});
 
-    let res = lambda!({y_fact} {nats[3]})
-        .purify()
-        .simplify(true)?
-        .to_owned();
-    eprintln!("{}", res);
-    assert_eq!(res, nats[6].purify());
+    // the arithmetic is accelerated by `optimize`, i. e. `simplify(true)`
+    let mut expected = 1;
+    for (n, nat) in nats.iter().enumerate().skip(1) {
+        expected *= n as u64;
+        let mut res = lambda!({y_fact} {nat});
+        let stats = res.simplify_with_stats(true)?;
+        assert_eq!(decode(&res, Encoding::Church), Some(expected));
+        eprintln!(
+            "{}! = {} ({} steps, {} accelerated)",
+            n, expected, stats.steps, stats.accelerations
+        );
+    }
 
     // if you try to simplify Y combinator ...
     eprintln!("simplify y: {}", y.simplify(true).unwrap_err()); // lamcalc::Error::SimplifyLimitExceeded
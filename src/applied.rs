@@ -0,0 +1,563 @@
+//! Applied lambda calculus with native constants and primitives.
+//!
+//! [`ExpExt`] is an expression whose free variables may be [constants](Const)
+//! like `42`, `true` and `"abc"`, or [primitives](Prim) like `+` and `if`,
+//! implemented in Rust. A primitive applied to all its arguments is
+//! delta-reduced once the arguments it inspects are constants, e. g.
+//! `+ 1 2` to `3` and `if true a b` to `a`, and is stuck otherwise, like
+//! `+ 1 true`.
+//!
+//! An expression of free variables named like the constants and primitives
+//! converts to [`ExpExt`] and back by [`From`] and
+//! [`to_string_exp`](Exp::to_string_exp). Literals are written as
+//!
+//! - `lambda!(# 42)`, `lambda!(# "abc")` and `lambda!(# true)` in the
+//!   [`lambda`](crate::lambda) macro,
+//! - `42`, `"abc"` and `true` in the parser with
+//!   [`native_literals`](ParseOptions::native_literals), see [`parse`].
+//!
+//! and [`Exp::encode`] converts to pure terms.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{applied::{self, Const, ExpExt}, lambda, numeral::Encoding};
+//! let mut e = applied::parse(r#"(\x. if (x < 10) (x * x) x) (1 + 2)"#).unwrap();
+//! e.simplify_applied().unwrap();
+//! assert_eq!(e, ExpExt::from(lambda!(# 9)));
+//! assert_eq!(e.as_const(), Some(&Const::Int(9)));
+//!
+//! // the same computation on Church numerals and booleans
+//! let mut e = applied::parse(r#"if (2 < 10) (2 * 2) 2"#).unwrap().encode(Encoding::Church).unwrap();
+//! e.simplify(false).unwrap();
+//! assert_eq!(e, lambda!(4));
+//! ```
+use crate::{
+    builder, lambda,
+    numeral::{self, Encoding},
+    parser::{Assoc, Fixity, ParseOptions},
+    prelude, Error, Exp, Ident, SIMPLIFY_LIMIT,
+};
+use std::collections::HashMap;
+
+/// A native constant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Const {
+    /// Integer, like `42`
+    Int(i64),
+    /// Boolean, `true` or `false`
+    Bool(bool),
+    /// String, like `"abc"`
+    Str(String),
+}
+
+/// A primitive operator on constants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prim {
+    /// `+ m n`
+    Add,
+    /// `- m n`
+    Sub,
+    /// `* m n`
+    Mul,
+    /// `/ m n`, stuck if `n` is 0
+    Div,
+    /// `% m n`, stuck if `n` is 0
+    Rem,
+    /// `== m n`
+    Eq,
+    /// `< m n`
+    Lt,
+    /// `<= m n`
+    Leq,
+    /// `not b`
+    Not,
+    /// `and p q`
+    And,
+    /// `or p q`
+    Or,
+    /// `if b t f`, which inspects `b` only
+    If,
+    /// `++ s t`, concatenation of strings
+    Concat,
+    /// `length s`, number of characters of a string
+    Length,
+}
+
+/// Every primitive, in order.
+const PRIMS: [Prim; 14] = [
+    Prim::Add,
+    Prim::Sub,
+    Prim::Mul,
+    Prim::Div,
+    Prim::Rem,
+    Prim::Eq,
+    Prim::Lt,
+    Prim::Leq,
+    Prim::Not,
+    Prim::And,
+    Prim::Or,
+    Prim::If,
+    Prim::Concat,
+    Prim::Length,
+];
+
+/// Identifier of [`ExpExt`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Atom {
+    /// A variable
+    Name(String),
+    /// A constant, which is a free variable
+    Const(Const),
+    /// A primitive, which is a free variable
+    Prim(Prim),
+}
+
+/// Expression of the applied lambda calculus.
+pub type ExpExt = Exp<Atom>;
+
+impl Prim {
+    /// Name of the primitive, e. g. `+`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Prim::Add => "+",
+            Prim::Sub => "-",
+            Prim::Mul => "*",
+            Prim::Div => "/",
+            Prim::Rem => "%",
+            Prim::Eq => "==",
+            Prim::Lt => "<",
+            Prim::Leq => "<=",
+            Prim::Not => "not",
+            Prim::And => "and",
+            Prim::Or => "or",
+            Prim::If => "if",
+            Prim::Concat => "++",
+            Prim::Length => "length",
+        }
+    }
+    /// The primitive named `name`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        PRIMS.into_iter().find(|prim| prim.name() == name)
+    }
+    /// Number of arguments.
+    pub fn arity(&self) -> usize {
+        match self {
+            Prim::Not | Prim::Length => 1,
+            Prim::If => 3,
+            _ => 2,
+        }
+    }
+    /// Apply to the arguments, return `None` if it's stuck.
+    fn apply(&self, args: &[&ExpExt]) -> Option<ExpExt> {
+        use Const::*;
+        let arg = |i: usize| args[i].as_const();
+        let res = match (self, arg(0)?) {
+            (Prim::If, Bool(b)) => return Some(args[if *b { 1 } else { 2 }].clone()),
+            (Prim::Not, Bool(b)) => Bool(!b),
+            (Prim::Length, Str(s)) => Int(s.chars().count() as i64),
+            (Prim::And, Bool(p)) => match arg(1)? {
+                Bool(q) => Bool(*p && *q),
+                _ => return None,
+            },
+            (Prim::Or, Bool(p)) => match arg(1)? {
+                Bool(q) => Bool(*p || *q),
+                _ => return None,
+            },
+            (Prim::Concat, Str(s)) => match arg(1)? {
+                Str(t) => Str(format!("{}{}", s, t)),
+                _ => return None,
+            },
+            (_, Int(m)) => {
+                let Int(n) = arg(1)? else {
+                    return None;
+                };
+                match self {
+                    Prim::Add => Int(m.checked_add(*n)?),
+                    Prim::Sub => Int(m.checked_sub(*n)?),
+                    Prim::Mul => Int(m.checked_mul(*n)?),
+                    Prim::Div => Int(m.checked_div(*n)?),
+                    Prim::Rem => Int(m.checked_rem(*n)?),
+                    Prim::Eq => Bool(m == n),
+                    Prim::Lt => Bool(m < n),
+                    Prim::Leq => Bool(m <= n),
+                    _ => return None,
+                }
+            }
+            _ => return None,
+        };
+        Some(Exp::Var(Ident(Atom::Const(res), 0)))
+    }
+    /// Definition on Church numerals, booleans and Scott lists of numerals.
+    ///
+    /// Division and remainder by 0 diverge. Subtraction has none, since its
+    /// result may be negative, which numerals can't be.
+    fn encode(&self) -> Option<Exp<String>> {
+        let get = |name| prelude::get(name).expect("defined in the prelude");
+        let lt = lambda!(m. n. {get("Not")} ({get("Leq")} n m));
+        Some(match self {
+            Prim::Add => get("Plus"),
+            Prim::Sub => return None,
+            Prim::Mul => get("Mul"),
+            Prim::Div => {
+                lambda!({get("Y")} (div. m. n. {lt} m n 0 ({get("Succ")} (div ({get("Sub")} m n) n))))
+            }
+            Prim::Rem => lambda!({get("Y")} (rem. m. n. {lt} m n m (rem ({get("Sub")} m n) n))),
+            Prim::Eq => get("Eq"),
+            Prim::Lt => lt,
+            Prim::Leq => get("Leq"),
+            Prim::Not => get("Not"),
+            Prim::And => get("And"),
+            Prim::Or => get("Or"),
+            Prim::If => get("If"),
+            Prim::Concat => get("Append"),
+            Prim::Length => get("Length"),
+        })
+    }
+}
+
+impl Const {
+    /// Encode as a pure term, where integers are numerals of `encoding`,
+    /// booleans are Church booleans, and strings are Scott lists of the
+    /// code points of the characters.
+    ///
+    /// Return [`Error::NoEncoding`] for negative integers, and those above
+    /// [`Encoding::max_literal`].
+    pub fn encode(&self, encoding: Encoding) -> Result<Exp<String>, Error> {
+        Ok(match self {
+            Const::Int(n) => match u64::try_from(*n) {
+                Ok(n) if n <= encoding.max_literal() => numeral::encode(n, encoding),
+                Ok(_) => {
+                    let reason = format!("exceeds {}", encoding.max_literal());
+                    return Err(Error::NoEncoding(n.to_string(), reason));
+                }
+                Err(_) => {
+                    let reason = String::from("negative integers aren't numerals");
+                    return Err(Error::NoEncoding(n.to_string(), reason));
+                }
+            },
+            Const::Bool(true) => lambda!(x.y.x),
+            Const::Bool(false) => lambda!(x.y.y),
+            Const::Str(s) => {
+                let mut res = lambda!(nil.cons.nil);
+                for c in s.chars().rev() {
+                    let c = numeral::encode(c as u64, encoding);
+                    res = lambda!(nil. cons. cons {c} {res});
+                }
+                res
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for Const {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Const::Int(n) => n.fmt(f),
+            Const::Bool(b) => b.fmt(f),
+            Const::Str(s) => write!(f, "\"{}\"", s),
+        }
+    }
+}
+
+impl Atom {
+    /// The atom named `name`, i. e. a constant for literals like `42`,
+    /// `true` and `"abc"`, a primitive for names like `+`, or a variable.
+    pub fn from_name(name: String) -> Self {
+        if let (false, Ok(n)) = (name.starts_with('+'), name.parse()) {
+            return Atom::Const(Const::Int(n));
+        }
+        if let Ok(b) = name.parse() {
+            return Atom::Const(Const::Bool(b));
+        }
+        if let Some(s) = name.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+            return Atom::Const(Const::Str(s.to_string()));
+        }
+        match Prim::from_name(&name) {
+            Some(prim) => Atom::Prim(prim),
+            None => Atom::Name(name),
+        }
+    }
+}
+
+impl std::fmt::Display for Atom {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Atom::Name(name) => name.fmt(f),
+            Atom::Const(c) => c.fmt(f),
+            Atom::Prim(prim) => prim.name().fmt(f),
+        }
+    }
+}
+
+impl From<Exp<String>> for ExpExt {
+    /// Convert free variables named like constants and primitives, see
+    /// [`Atom::from_name`].
+    fn from(mut exp: Exp<String>) -> Self {
+        // parts are taken out, as expressions can't be destructured by value
+        let take = |exp: &mut Exp<String>| std::mem::replace(exp, Exp::var("", 0)).into();
+        match &mut exp {
+            Exp::Var(Ident(name, 0)) => Exp::Var(Ident(Atom::from_name(std::mem::take(name)), 0)),
+            Exp::Var(Ident(name, code)) => Exp::Var(Ident(Atom::Name(std::mem::take(name)), *code)),
+            Exp::Abs(Ident(name, code), body) => Exp::Abs(
+                Ident(Atom::Name(std::mem::take(name)), *code),
+                Box::new(take(body)),
+            ),
+            Exp::App(func, body) => Exp::App(Box::new(take(func)), Box::new(take(body))),
+        }
+    }
+}
+
+impl From<Const> for ExpExt {
+    fn from(c: Const) -> Self {
+        Exp::Var(Ident(Atom::Const(c), 0))
+    }
+}
+
+impl std::fmt::Display for ExpExt {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.to_string_exp().fmt(f)
+    }
+}
+
+/// Options parsing expressions with native literals, see
+/// [`ParseOptions::native_literals`], and the operators of the primitives,
+/// where `*`, `/` and `%` bind tighter than `+` and `-`, then `++`, then
+/// `==`, `<` and `<=`.
+pub fn parse_options() -> ParseOptions {
+    let fixity = |assoc, precedence, prim: Prim| {
+        let name = prim.name().to_string();
+        (
+            name.clone(),
+            Fixity {
+                assoc,
+                precedence,
+                name,
+            },
+        )
+    };
+    ParseOptions {
+        native_literals: true,
+        fixities: HashMap::from([
+            fixity(Assoc::Left, 7, Prim::Mul),
+            fixity(Assoc::Left, 7, Prim::Div),
+            fixity(Assoc::Left, 7, Prim::Rem),
+            fixity(Assoc::Left, 6, Prim::Add),
+            fixity(Assoc::Left, 6, Prim::Sub),
+            fixity(Assoc::Right, 5, Prim::Concat),
+            fixity(Assoc::None, 4, Prim::Eq),
+            fixity(Assoc::None, 4, Prim::Lt),
+            fixity(Assoc::None, 4, Prim::Leq),
+        ]),
+        ..Default::default()
+    }
+}
+
+/// Parse an expression by [`parse_options`].
+pub fn parse(lambda: &str) -> Result<ExpExt, Error> {
+    let (exp, _, _) = parse_options().parse_exp_with_spans(lambda)?;
+    Ok(exp.into())
+}
+
+impl Exp<Atom> {
+    /// The constant, if it's one.
+    pub fn as_const(&self) -> Option<&Const> {
+        match self {
+            Exp::Var(Ident(Atom::Const(c), 0)) => Some(c),
+            _ => None,
+        }
+    }
+    /// Try making delta reduction, i. e. applying a primitive to its
+    /// arguments, return false if nothing changed.
+    pub fn delta_reduce(&mut self) -> bool {
+        let mut args = vec![];
+        let mut head: &ExpExt = self;
+        while let Exp::App(func, arg) = head {
+            args.push(arg.as_ref());
+            head = func;
+        }
+        args.reverse();
+        let Exp::Var(Ident(Atom::Prim(prim), 0)) = head else {
+            return false;
+        };
+        if args.len() != prim.arity() {
+            return false;
+        }
+        match prim.apply(&args) {
+            Some(res) => {
+                *self = res;
+                true
+            }
+            None => false,
+        }
+    }
+    /// Like [`Exp::eval_normal_order`], additionally delta-reducing
+    /// primitives before beta-reduction.
+    ///
+    /// return `false` if nothing changes, otherwise `true`.
+    pub fn eval_applied(&mut self, eta_reduce: bool) -> bool {
+        if self.delta_reduce() || self.beta_reduce() {
+            return true;
+        }
+        if eta_reduce && self.eta_reduce() {
+            return true;
+        }
+        match self {
+            Exp::Var(_) => false,
+            Exp::Abs(_, body) => body.eval_applied(eta_reduce),
+            Exp::App(func, body) => func.eval_applied(eta_reduce) || body.eval_applied(eta_reduce),
+        }
+    }
+    /// Simplify by [`Exp::eval_applied`] for at most [`SIMPLIFY_LIMIT`]
+    /// times.
+    pub fn simplify_applied(&mut self) -> Result<&mut Self, Error> {
+        for _ in 0..SIMPLIFY_LIMIT {
+            if !self.eval_applied(false) {
+                return Ok(self);
+            }
+        }
+        Err(Error::SimplifyLimitExceeded)
+    }
+    /// Convert to a pure term, encoding constants by [`Const::encode`] and
+    /// primitives by their definitions on Church numerals, booleans and
+    /// Scott lists.
+    ///
+    /// Note that division and remainder by 0 diverge in pure terms.
+    ///
+    /// Return [`Error::NoEncoding`] for negative integers, for subtraction,
+    /// whose result may be negative, and for primitives on numerals other
+    /// than Church numerals.
+    pub fn encode(&self, encoding: Encoding) -> Result<Exp<String>, Error> {
+        Ok(match self {
+            Exp::Var(Ident(Atom::Const(c), 0)) => c.encode(encoding)?,
+            Exp::Var(Ident(Atom::Prim(prim), 0)) => {
+                let reason = match encoding {
+                    Encoding::Church => match prim.encode() {
+                        Some(exp) => return Ok(exp),
+                        None => String::from("the result may be negative"),
+                    },
+                    _ => format!("primitives aren't defined on {:?} numerals", encoding),
+                };
+                return Err(Error::NoEncoding(prim.name().to_string(), reason));
+            }
+            Exp::Var(Ident(atom, code)) => Exp::Var(Ident(atom.to_string(), *code)),
+            Exp::Abs(Ident(atom, code), body) => Exp::Abs(
+                Ident(atom.to_string(), *code),
+                Box::new(body.encode(encoding)?),
+            ),
+            Exp::App(func, body) => {
+                builder::app(vec![func.encode(encoding)?, body.encode(encoding)?])
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Atom, Const, ExpExt, Prim};
+    use crate::{lambda, numeral::Encoding, Error, Exp};
+
+    fn eval(s: &str) -> Result<ExpExt, Error> {
+        let mut e = parse(s)?;
+        e.simplify_applied()?;
+        Ok(e)
+    }
+
+    #[test]
+    fn test_delta() -> Result<(), Error> {
+        let int = |n| Some(Const::Int(n));
+        assert_eq!(eval("1 + 2 * 3")?.as_const().cloned(), int(7));
+        assert_eq!(eval("1 - 3")?.as_const().cloned(), int(-2));
+        assert_eq!(eval("7 % 3")?.as_const().cloned(), int(1));
+        assert_eq!(
+            eval("length (\"ab\" ++ \"c\")")?.as_const().cloned(),
+            int(3)
+        );
+        assert_eq!(
+            eval("and (1 <= 1) (not (1 == 2))")?.as_const().cloned(),
+            Some(Const::Bool(true))
+        );
+        // `if` doesn't inspect the discarded branch
+        assert_eq!(eval("if false (1 / 0) a")?, ExpExt::from(lambda!(a)));
+
+        // stuck primitives
+        assert_eq!(eval("1 / 0")?, parse("1 / 0")?);
+        assert_eq!(eval("1 + true")?, parse("1 + true")?);
+        assert_eq!(
+            eval("\\x. x + 1")?.to_string(),
+            parse("\\x. x + 1")?.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_conversion() -> Result<(), Error> {
+        let e = ExpExt::from(lambda!(x. "+" x (# 1) (# "a") (# true) y));
+        let Exp::Abs(_, body) = &e else { panic!() };
+        let mut atoms = vec![];
+        let mut cur = body.as_ref();
+        while let Exp::App(func, arg) = cur {
+            atoms.push(arg.into_ident().unwrap().0.clone());
+            cur = func;
+        }
+        assert_eq!(
+            atoms,
+            [
+                Atom::Name(String::from("y")),
+                Atom::Const(Const::Bool(true)),
+                Atom::Const(Const::Str(String::from("a"))),
+                Atom::Const(Const::Int(1)),
+                Atom::Name(String::from("x")),
+            ]
+        );
+        assert_eq!(cur.into_ident().unwrap().0, Atom::Prim(Prim::Add));
+        assert_eq!(
+            e.to_string_exp(),
+            lambda!(x. "+" x (# 1) (# "a") (# true) y)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode() -> Result<(), Error> {
+        for (s, res) in [
+            ("2 + 3 * 4", lambda!(14)),
+            ("7 / 2", lambda!(3)),
+            ("7 % 2", lambda!(1)),
+            ("if (2 < 1) a b", lambda!(b)),
+            ("or (2 == 3) (2 <= 3)", lambda!(x.y.x)),
+            ("length (\"ab\" ++ \"c\")", lambda!(3)),
+        ] {
+            let mut e = parse(s)?.encode(Encoding::Church)?;
+            e.simplify(false)?;
+            assert_eq!(e, res, "{}", s);
+        }
+        assert_eq!(
+            parse("\"ab\"")?.encode(Encoding::Scott)?,
+            lambda!(nil. cons. cons {crate::numeral::encode(97, Encoding::Scott)} (nil. cons. cons {crate::numeral::encode(98, Encoding::Scott)} (nil. cons. nil)))
+        );
+        assert!(matches!(
+            parse("1 - 2")?.encode(Encoding::Binary),
+            Err(Error::NoEncoding(..))
+        ));
+        assert!(matches!(
+            eval("1 - 2")?.encode(Encoding::Church),
+            Err(Error::NoEncoding(..))
+        ));
+
+        assert!(matches!(
+            parse("1000")?.encode(Encoding::Church),
+            Err(Error::NoEncoding(..))
+        ));
+        assert!(parse("1000")?.encode(Encoding::Binary).is_ok());
+
+        // subtraction isn't truncated to give a different result
+        let s = "1 - 3";
+        assert_eq!(eval(s)?.as_const(), Some(&Const::Int(-2)));
+        let Err(Error::NoEncoding(name, _)) = parse(s)?.encode(Encoding::Church) else {
+            panic!("expect no encoding")
+        };
+        assert_eq!(name, "-");
+        Ok(())
+    }
+}
@@ -0,0 +1,249 @@
+//! [Binary lambda calculus](https://tromp.github.io/cl/Binary_lambda_calculus.html),
+//! Tromp's serialization of closed expressions by their De Bruijn indices:
+//!
+//! | expression | bits              |
+//! |------------|-------------------|
+//! | `λM`       | `00` M            |
+//! | `M N`      | `01` M N          |
+//! | index `i`  | `1` repeated `i` times, then `0` |
+//!
+//! A program is run on its input bits as a list, where the bit `0` is
+//! `λx. λy. x`, `1` is `λx. λy. y`, `[]` is `λx. λy. y` and `[x, ..xs]` is
+//! the pair `λf. f x xs`, and the normal form of the result is the output
+//! in the same encoding.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{blc, lambda, Exp};
+//! let bits = |s: &str| s.chars().map(|c| c == '1').collect::<Vec<_>>();
+//! let id = lambda!(x. x);
+//! assert_eq!(id.to_blc_bits(), Some(bits("0010")));
+//!
+//! // the bits after the program are its input
+//! let source = bits("0010110");
+//! let (program, input) = Exp::from_blc(&source).unwrap();
+//! assert_eq!(program, id.purify());
+//! assert_eq!(blc::run(&program, input, None).unwrap(), Some(bits("110")));
+//! ```
+use crate::{
+    decode::{spine, strip_abs},
+    Error, EvalOptions, Exp, Ident,
+};
+
+impl<T: Clone + Eq> Exp<T> {
+    /// Serialize into binary lambda calculus, return `None` if there are free
+    /// variables.
+    pub fn to_blc_bits(&self) -> Option<Vec<bool>> {
+        fn write<T: Clone + Eq>(exp: &Exp<T>, bits: &mut Vec<bool>) -> Option<()> {
+            match exp {
+                Exp::Var(Ident(_, 0)) => return None,
+                Exp::Var(Ident(_, code)) => {
+                    bits.extend((0..*code).map(|_| true));
+                    bits.push(false);
+                }
+                Exp::Abs(_, body) => {
+                    bits.extend([false, false]);
+                    write(body, bits)?;
+                }
+                Exp::App(func, body) => {
+                    bits.extend([false, true]);
+                    write(func, bits)?;
+                    write(body, bits)?;
+                }
+            }
+            Some(())
+        }
+        let mut bits = vec![];
+        write(self, &mut bits)?;
+        Some(bits)
+    }
+    /// Number of bits in binary lambda calculus, return `None` if there are
+    /// free variables.
+    pub fn blc_size(&self) -> Option<usize> {
+        match self {
+            Exp::Var(Ident(_, 0)) => None,
+            Exp::Var(Ident(_, code)) => Some(*code as usize + 1),
+            Exp::Abs(_, body) => Some(2 + body.blc_size()?),
+            Exp::App(func, body) => Some(2 + func.blc_size()? + body.blc_size()?),
+        }
+    }
+}
+
+impl Exp<()> {
+    /// Deserialize a closed expression from the beginning of `bits`, return
+    /// it with the rest of the bits.
+    ///
+    /// Return [`Error::InvalidBlc`] if the bits end before the expression
+    /// does, or an index exceeds the enclosing abstractions.
+    pub fn from_blc(bits: &[bool]) -> Result<(Exp<()>, &[bool]), Error> {
+        fn next(bits: &[bool], pos: &mut usize) -> Result<bool, Error> {
+            let bit = bits.get(*pos).copied();
+            *pos += 1;
+            bit.ok_or_else(|| Error::InvalidBlc(bits.len(), String::from("unexpected end")))
+        }
+        fn read(bits: &[bool], pos: &mut usize, depth: u32) -> Result<Exp<()>, Error> {
+            if next(bits, pos)? {
+                let start = *pos - 1;
+                let mut code = 1;
+                while next(bits, pos)? {
+                    code += 1;
+                }
+                if code > depth {
+                    let reason = format!("index {} under {} abstractions", code, depth);
+                    return Err(Error::InvalidBlc(start, reason));
+                }
+                return Ok(Exp::var((), code));
+            }
+            if next(bits, pos)? {
+                let func = read(bits, pos, depth)?;
+                Ok(Exp::app(func, read(bits, pos, depth)?))
+            } else {
+                Ok(Exp::abs((), read(bits, pos, depth + 1)?))
+            }
+        }
+        let mut pos = 0;
+        let exp = read(bits, &mut pos, 0)?;
+        Ok((exp, &bits[pos..]))
+    }
+}
+
+/// Pack bits into bytes, the most significant bit first, padding the last
+/// byte with zeros.
+pub fn pack(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .enumerate()
+                .fold(0, |byte, (i, bit)| byte | ((*bit as u8) << (7 - i)))
+        })
+        .collect()
+}
+
+/// Unpack bytes into bits, the most significant bit first.
+pub fn unpack(bytes: &[u8]) -> Vec<bool> {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+        .collect()
+}
+
+/// Encode bits as a list.
+pub fn encode_bits(bits: &[bool]) -> Exp<()> {
+    // `λx. λy. y`, being both `1` and `[]`
+    let nil = || Exp::abs((), Exp::abs((), Exp::var((), 1)));
+    bits.iter().rev().fold(nil(), |list, bit| {
+        let bit = if *bit {
+            nil()
+        } else {
+            Exp::abs((), Exp::abs((), Exp::var((), 2)))
+        };
+        Exp::abs((), Exp::app(Exp::app(Exp::var((), 1), bit), list))
+    })
+}
+
+/// Decode a list of bits in normal form.
+pub fn decode_bits<T: Clone + Eq>(exp: &Exp<T>) -> Option<Vec<bool>> {
+    let mut res = vec![];
+    let mut cur = exp;
+    loop {
+        // booleans only refer to their own abstractions, so neither the bits
+        // nor the rest need to be moved out of the pairs
+        let (bit, rest) = match strip_abs(cur, 1).map(spine) {
+            Some((Exp::Var(Ident(_, 1)), args)) if args.len() == 2 => (args[0], args[1]),
+            _ => return (cur.as_bool() == Some(false)).then_some(res),
+        };
+        res.push(!bit.as_bool()?);
+        cur = rest;
+    }
+}
+
+/// Apply `program` to the list of `input` bits, return the output bits
+/// decoded from the normal form, or `None` if it's not a list of bits.
+///
+/// The normal form is reached by at most `limit` reductions, `None` for no
+/// limit, as programs take steps in proportion to their input.
+pub fn run(
+    program: &Exp<()>,
+    input: &[bool],
+    limit: Option<u64>,
+) -> Result<Option<Vec<bool>>, Error> {
+    let mut exp = Exp::app(program.clone(), encode_bits(input));
+    exp.simplify_with(&EvalOptions {
+        limit,
+        ..Default::default()
+    })?;
+    Ok(decode_bits(&exp))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_bits, encode_bits, pack, run, unpack};
+    use crate::{lambda, prelude, Error, Exp, SIMPLIFY_LIMIT};
+
+    fn bits(s: &str) -> Vec<bool> {
+        s.chars().map(|c| c == '1').collect()
+    }
+
+    #[test]
+    fn test_serialize() -> Result<(), Error> {
+        let s = prelude::get("S").unwrap();
+        assert_eq!(s.to_blc_bits(), Some(bits("00000001011110100111010")));
+        assert_eq!(s.blc_size(), Some(23));
+        assert_eq!(lambda!(x.y).to_blc_bits(), None);
+
+        for name in ["Pair", "Plus", "Pred", "Y", "Map"] {
+            let exp = prelude::get(name).unwrap();
+            let bits = exp.to_blc_bits().unwrap();
+            assert_eq!(bits.len(), exp.blc_size().unwrap());
+            // padding is left over
+            let packed = unpack(&pack(&bits));
+            let (res, rest) = Exp::from_blc(&packed)?;
+            assert_eq!(res, exp.purify(), "{}", name);
+            assert!(rest.len() < 8 && rest.iter().all(|b| !b));
+        }
+
+        assert_eq!(pack(&bits("0000000101")), [0b00000001, 0b01000000]);
+        assert!(matches!(
+            Exp::from_blc(&bits("0001")),
+            Err(Error::InvalidBlc(4, _))
+        ));
+        assert!(matches!(
+            Exp::from_blc(&bits("001100")),
+            Err(Error::InvalidBlc(2, _))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_run() -> Result<(), Error> {
+        let input = bits("0110");
+        assert_eq!(decode_bits(&encode_bits(&input)), Some(input.clone()));
+
+        // the first bit, negated
+        let (t, f) = (lambda!(x.y.x), lambda!(x.y.y));
+        let program = lambda!(l. z. z (l {t} {f} {t}) {f}).purify();
+        assert_eq!(run(&program, &input, None)?, Some(bits("1")));
+        assert_eq!(run(&lambda!(l.l).purify(), &input, None)?, Some(input));
+        assert_eq!(run(&lambda!(l.x.x).purify(), &[], None)?, None);
+
+        // every bit negated, recursively, read from its serialization
+        // followed by the input
+        let negate = lambda!({prelude::get("Y").unwrap()} (rec. l.
+            l (x. xs. nil. p. p (x {f} {t}) (rec xs)) {f}
+        ));
+        let input: Vec<bool> = (0..200).map(|i| i % 3 == 0).collect();
+        let mut source = negate.to_blc_bits().unwrap();
+        source.extend(&input);
+        let (program, rest) = Exp::from_blc(&source)?;
+        assert_eq!(rest, input);
+        let negated: Vec<bool> = input.iter().map(|b| !b).collect();
+        assert_eq!(run(&program, rest, None)?, Some(negated));
+        assert!(matches!(
+            run(&program, rest, Some(SIMPLIFY_LIMIT as u64)),
+            Err(Error::SimplifyLimitExceeded)
+        ));
+        Ok(())
+    }
+}
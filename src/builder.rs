@@ -59,12 +59,105 @@ where
     Exp::Var(Ident(v, 0))
 }
 
+#[doc(hidden)]
+pub trait Literal {
+    fn into_exp(self) -> Exp<String>;
+}
+
+// Only `u64` is implemented, so that unsuffixed integers are inferred as
+// it, and negative ones like `-1` are rejected at compile time.
+impl Literal for u64 {
+    fn into_exp(self) -> Exp<String> {
+        crate::numeral::encode(self, crate::numeral::Encoding::Church)
+    }
+}
+
+impl Literal for &str {
+    fn into_exp(self) -> Exp<String> {
+        unbounded_var(String::from(self))
+    }
+}
+
+/// Check of a literal at compile time.
+#[doc(hidden)]
+pub struct LiteralCheck<L>(pub L);
+
+impl LiteralCheck<u64> {
+    pub const fn is_valid(&self) -> bool {
+        self.0 <= crate::numeral::MAX_UNARY
+    }
+}
+
+impl LiteralCheck<&str> {
+    pub const fn is_valid(&self) -> bool {
+        true
+    }
+}
+
+#[doc(hidden)]
+pub fn literal<L: Literal>(l: L) -> Exp<String> {
+    l.into_exp()
+}
+
+#[doc(hidden)]
+pub trait NativeLiteral {
+    fn name(self) -> String;
+}
+
+macro_rules! impl_native_literal {
+    ($( $t:ty ),+) => {
+        $(
+            impl NativeLiteral for $t {
+                fn name(self) -> String {
+                    self.to_string()
+                }
+            }
+        )+
+    };
+}
+
+impl_native_literal!(i32, i64, u32, u64, usize, bool);
+
+impl NativeLiteral for &str {
+    fn name(self) -> String {
+        format!("\"{}\"", self)
+    }
+}
+
+#[doc(hidden)]
+pub fn native<L: NativeLiteral>(l: L) -> Exp<String> {
+    unbounded_var(l.name())
+}
+
 /// Build lambda expression with [`String`] identifier conveniently.
 /// Generally:
 ///
-/// 1. Dot `.` can be used to define abstraction.
+/// 1. Dot `.` can be used to define abstraction, `x y. e` for `x. y. e`.
 /// 2. Parentheses can be used to denote subexpression.
 /// 3. Application is left associated by default.
+/// 4. `let x = e1 in e2` for `(x. e2) e1`, where `e1` is a single token
+///    tree, e. g. an identifier or a parenthesized expression.
+/// 5. Natural numbers like `42` are Church numerals. For other encodings
+///    use [`numeral::encode`](crate::numeral::encode) in braces. Negative
+///    numbers and those above [`MAX_UNARY`](crate::numeral::MAX_UNARY)
+///    don't compile:
+///
+///    ```compile_fail
+///    # use lamcalc::lambda;
+///    let n = lambda!(-1);
+///    ```
+///
+///    ```compile_fail
+///    # use lamcalc::lambda;
+///    let n = lambda!(9999999999);
+///    ```
+/// 6. String literals are identifiers, for names that aren't Rust identifiers,
+///    e. g. `lambda!("f'". "+" "f'" 1)`. Parameters of an abstraction are
+///    either all identifiers or all string literals.
+/// 7. `# 42`, `# "abc"` and `# true` are native literals of
+///    [`ExpExt`](crate::applied::ExpExt), i. e. free variables named `42`,
+///    `"abc"` and `true`. In applications they must be parenthesized, e. g.
+///    `lambda!(f (# 1))`.
 ///
 /// If you find an expression not parsed, try adding parentheses to subexpressions.
 ///
@@ -75,6 +168,13 @@ macro_rules! lambda {
     [($( $t:tt )+)] => {
         lambda![$( $t )+]
     };
+    // let binding
+    [let $v:ident = $e:tt in $( $t:tt )+] => {
+        $crate::builder::app(vec![
+            $crate::builder::abs(String::from(stringify!($v)), lambda![$( $t )+]),
+            lambda![$e],
+        ])
+    };
     // variable expression
     [{$v:expr}] => {
         $v.clone()
@@ -84,10 +184,33 @@ macro_rules! lambda {
     [$v:ident] => {
         $crate::builder::unbounded_var::<String>(String::from(stringify!($v)))
     };
+    // native literal
+    [# $l:literal] => {
+        $crate::builder::native($l)
+    };
+    // literal
+    [$l:literal] => {{
+        const _: () = assert!(
+            $crate::builder::LiteralCheck($l).is_valid(),
+            "numerals can't exceed `numeral::MAX_UNARY`"
+        );
+        $crate::builder::literal($l)
+    }};
+    // abstraction of quoted identifier
+    [$v:literal.$( $t:tt )+] => {
+        $crate::builder::abs(String::from($v), lambda![$( $t )+])
+    };
     // abstraction
     [$v:ident.$( $t:tt )+] => {
         $crate::builder::abs(String::from(stringify!($v)), lambda![$( $t )+])
     };
+    // abstraction of multiple arguments
+    [$v:ident $( $vs:ident )+ . $( $t:tt )+] => {
+        $crate::builder::abs(String::from(stringify!($v)), lambda![$( $vs )+ . $( $t )+])
+    };
+    [$v:literal $( $vs:literal )+ . $( $t:tt )+] => {
+        $crate::builder::abs(String::from($v), lambda![$( $vs )+ . $( $t )+])
+    };
     // application
     [$l:tt $( $t:tt )+] => {
         $crate::builder::app(vec![lambda![$l], $( lambda![$t] ),+])
@@ -116,4 +239,15 @@ mod tests {
             )
         );
     }
+    #[test]
+    fn test_sugar() {
+        assert_eq!(lambda!(x y z. x z (y z)), lambda!(x. y. z. x z (y z)));
+        assert_eq!(
+            lambda!(let i = (x. x) in let k = (x y. x) in k i),
+            lambda!((i. (k. k i) (x. y. x)) (x. x))
+        );
+        let tt = lambda!(x y. x);
+        assert_eq!(lambda!(let t = {tt} in t), lambda!((t. t) {tt}));
+        assert_eq!(lambda!(f 2), lambda!(f (f. x. f (f x))));
+    }
 }
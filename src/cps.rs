@@ -0,0 +1,258 @@
+//! Continuation-passing style transformations.
+//!
+//! A transformed expression takes a continuation `k` to which it passes its
+//! value, so that evaluating it under any strategy follows the strategy it
+//! is transformed for. Values `V` are transformed into `Ψ(V)`, where
+//! `Ψ(λx. M) = λx. [M]`, and the result of `[M] (λv. v)` is `Ψ(V)` if `M`
+//! evaluates to `V`.
+//!
+//! | `M`      | call-by-value `[M]`                  | call-by-name `[M]`          |
+//! |----------|--------------------------------------|-----------------------------|
+//! | `x`      | `λk. k x`                            | `x`                         |
+//! | `λx. N`  | `λk. k (λx. [N])`                    | `λk. k (λx. [N])`           |
+//! | `N L`    | `λk. [N] (λm. [L] (λn. m n k))`      | `λk. [N] (λm. m [L] k)`     |
+//!
+//! Free variables are values in call-by-value, and computations in
+//! call-by-name.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{cps, lambda};
+//! let m = lambda!(f x);
+//! assert_eq!(
+//!     cps::call_by_value(&m).to_string(),
+//!     "λk. (λk. k f) λm. (λk. k x) λn. (m n) k"
+//! );
+//! assert_eq!(cps::call_by_name(&m).to_string(), "λk. f λm. (m x) k");
+//! // without administrative redexes
+//! assert_eq!(cps::call_by_value_one_pass(&m).to_string(), "λk. (f x) k");
+//! ```
+use crate::{Exp, Ident};
+use std::cell::Cell;
+
+// The transformations build expressions whose abstractions and bound
+// variables are identified by unique numbers, so that values can be moved
+// under other abstractions as they are, and replaced by De Bruijn indices
+// at last.
+
+/// Generator of unique identifiers of abstractions.
+#[derive(Default)]
+struct Fresh(Cell<u32>);
+
+impl Fresh {
+    /// Abstraction whose body is built from the identifier of its variable.
+    fn lam(&self, name: &str, body: impl FnOnce(u32) -> Exp<String>) -> Exp<String> {
+        let id = self.0.get() + 1;
+        self.0.set(id);
+        Exp::Abs(Ident(name.to_string(), id), Box::new(body(id)))
+    }
+}
+
+/// Variable of the source in the environment of identifiers of its
+/// enclosing abstractions.
+fn source_var(name: &str, code: u32, env: &[u32]) -> Exp<String> {
+    match code {
+        0 => Exp::var(name, 0),
+        code => Exp::var(name, env[env.len() - code as usize]),
+    }
+}
+
+/// Replace identifiers by De Bruijn indices.
+fn into_indices(exp: &mut Exp<String>, scope: &mut Vec<u32>) {
+    match exp {
+        Exp::Var(Ident(_, 0)) => {}
+        Exp::Var(Ident(_, code)) => {
+            let pos = scope.iter().rposition(|id| id == code).expect("bound");
+            *code = (scope.len() - pos) as u32;
+        }
+        Exp::Abs(Ident(_, id), body) => {
+            scope.push(std::mem::take(id));
+            into_indices(body, scope);
+            scope.pop();
+        }
+        Exp::App(func, body) => {
+            into_indices(func, scope);
+            into_indices(body, scope);
+        }
+    }
+}
+
+fn finish(mut exp: Exp<String>) -> Exp<String> {
+    into_indices(&mut exp, &mut vec![]);
+    exp.rename_for_display();
+    exp
+}
+
+fn plotkin(fresh: &Fresh, exp: &Exp<String>, env: &[u32], by_value: bool) -> Exp<String> {
+    match exp {
+        Exp::Var(Ident(name, code)) if by_value => fresh.lam("k", |k| {
+            Exp::app(Exp::var("k", k), source_var(name, *code, env))
+        }),
+        Exp::Var(Ident(name, code)) => source_var(name, *code, env),
+        Exp::Abs(Ident(name, _), body) => fresh.lam("k", |k| {
+            let value = fresh.lam(name, |x| {
+                plotkin(fresh, body, &[env, &[x]].concat(), by_value)
+            });
+            Exp::app(Exp::var("k", k), value)
+        }),
+        Exp::App(func, body) => fresh.lam("k", |k| {
+            let func = plotkin(fresh, func, env, by_value);
+            let cont = fresh.lam("m", |m| match by_value {
+                true => Exp::app(
+                    plotkin(fresh, body, env, by_value),
+                    fresh.lam("n", |n| {
+                        Exp::app(
+                            Exp::app(Exp::var("m", m), Exp::var("n", n)),
+                            Exp::var("k", k),
+                        )
+                    }),
+                ),
+                false => Exp::app(
+                    Exp::app(Exp::var("m", m), plotkin(fresh, body, env, by_value)),
+                    Exp::var("k", k),
+                ),
+            });
+            Exp::app(func, cont)
+        }),
+    }
+}
+
+/// Plotkin's call-by-value transformation.
+pub fn call_by_value(exp: &Exp<String>) -> Exp<String> {
+    finish(plotkin(&Fresh::default(), exp, &[], true))
+}
+
+/// Plotkin's call-by-name transformation.
+pub fn call_by_name(exp: &Exp<String>) -> Exp<String> {
+    finish(plotkin(&Fresh::default(), exp, &[], false))
+}
+
+/// Continuation of the transformation, building the rest of the result from
+/// a value.
+type MetaCont<'a> = &'a dyn Fn(Exp<String>) -> Exp<String>;
+
+/// `Ψ` of a source value in one pass.
+fn one_pass_value(fresh: &Fresh, exp: &Exp<String>, env: &[u32]) -> Exp<String> {
+    match exp {
+        Exp::Var(Ident(name, code)) => source_var(name, *code, env),
+        Exp::Abs(Ident(name, _), body) => fresh.lam(name, |x| {
+            fresh.lam("k", |k| {
+                one_pass_tail(fresh, body, &[env, &[x]].concat(), k)
+            })
+        }),
+        Exp::App(..) => unreachable!("applications aren't values"),
+    }
+}
+
+/// Transform in one pass, passing the value to the meta continuation.
+fn one_pass(fresh: &Fresh, exp: &Exp<String>, env: &[u32], cont: MetaCont) -> Exp<String> {
+    match exp {
+        Exp::App(func, body) => one_pass(fresh, func, env, &|m| {
+            one_pass(fresh, body, env, &|n| {
+                let k = fresh.lam("a", |a| cont(Exp::var("a", a)));
+                Exp::app(Exp::app(m.clone(), n), k)
+            })
+        }),
+        value => cont(one_pass_value(fresh, value, env)),
+    }
+}
+
+/// Transform in one pass, passing the value to the continuation variable
+/// `k`.
+fn one_pass_tail(fresh: &Fresh, exp: &Exp<String>, env: &[u32], k: u32) -> Exp<String> {
+    match exp {
+        Exp::App(func, body) => one_pass(fresh, func, env, &|m| {
+            one_pass(fresh, body, env, &|n| {
+                Exp::app(Exp::app(m.clone(), n), Exp::var("k", k))
+            })
+        }),
+        value => Exp::app(Exp::var("k", k), one_pass_value(fresh, value, env)),
+    }
+}
+
+/// Danvy and Filinski's one-pass call-by-value transformation, which is
+/// [`call_by_value`] without administrative redexes, i. e. those introduced
+/// by the transformation.
+pub fn call_by_value_one_pass(exp: &Exp<String>) -> Exp<String> {
+    let fresh = Fresh::default();
+    finish(fresh.lam("k", |k| one_pass_tail(&fresh, exp, &[], k)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call_by_name, call_by_value, call_by_value_one_pass};
+    use crate::{decode::unshift, lambda, prelude, Error, Exp, SIMPLIFY_LIMIT};
+
+    fn eval(
+        mut exp: Exp<String>,
+        step: fn(&mut Exp<String>) -> bool,
+    ) -> Result<Exp<String>, Error> {
+        for _ in 0..SIMPLIFY_LIMIT {
+            if !step(&mut exp) {
+                return Ok(exp);
+            }
+        }
+        Err(Error::SimplifyLimitExceeded)
+    }
+
+    /// `Ψ(V)`, taken from `[V] = λk. k Ψ(V)`.
+    fn psi(transform: fn(&Exp<String>) -> Exp<String>, value: &Exp<String>) -> Exp<String> {
+        let cps = transform(value);
+        let (_, arg) = cps.into_abs().unwrap().1.into_app().unwrap();
+        unshift(arg, 1, 0).unwrap()
+    }
+
+    fn normal(mut exp: Exp<String>) -> Result<Exp<()>, Error> {
+        exp.simplify(false)?;
+        Ok(exp.purify())
+    }
+
+    #[test]
+    fn test_simulation() -> Result<(), Error> {
+        let omega = lambda!((x. x x) (x. x x));
+        let exps = [
+            lambda!((x.y.x)(z.z)(w.w)),
+            lambda!((f. f (x. x)) (y. y y)),
+            lambda!({prelude::PLUS} 1 2),
+            lambda!((x. y. y) {omega}),
+            lambda!((x. (y. y) x) ((z. z z) (z. z))),
+        ];
+        type Transform = fn(&Exp<String>) -> Exp<String>;
+        let by_value: fn(&mut Exp<String>) -> bool = Exp::eval_call_by_value;
+        let by_name: fn(&mut Exp<String>) -> bool = Exp::eval_call_by_name;
+        // transformed expressions are evaluated by the other strategy
+        let cases: [(Transform, _, _); 3] = [
+            (call_by_value, by_value, by_name),
+            (call_by_value_one_pass, by_value, by_name),
+            (call_by_name, by_name, by_value),
+        ];
+        for (transform, strategy, other) in cases {
+            for m in &exps {
+                let direct = eval(m.clone(), strategy);
+                let cps = eval(Exp::app(transform(m), lambda!(v.v)), other);
+                match (direct, cps) {
+                    (Ok(v), Ok(res)) => {
+                        assert_eq!(normal(res)?, normal(psi(transform, &v))?, "{}", m)
+                    }
+                    (Err(_), Err(_)) => {}
+                    (direct, cps) => panic!("{}: {:?} but {:?}", m, direct, cps),
+                }
+            }
+        }
+        assert!(eval(lambda!((x. y. y) {omega}), by_value).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_administrative() -> Result<(), Error> {
+        for m in [lambda!(f (g x)), lambda!(x. f x (y. y x))] {
+            // the source is in normal form, so the redexes of the plain
+            // transformation are administrative
+            let mut e = call_by_value_one_pass(&m);
+            assert!(!e.eval_normal_order(false, false), "{}", e);
+            assert_eq!(normal(call_by_value(&m))?, e.purify());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+//! Algebraic data types encoded as lambda expressions.
+//!
+//! A declaration like `data List = Nil | Cons head tail:List` defines a
+//! constructor for every alternative and an eliminator `caseList`, where a
+//! field annotated by the type itself, like `tail:List`, is recursive.
+//!
+//! | encoding | `Nil`           | `Cons`                                  |
+//! |----------|-----------------|-----------------------------------------|
+//! | Scott    | `λnil. λcons. nil` | `λhead. λtail. λnil. λcons. cons head tail` |
+//! | Church   | `λnil. λcons. nil` | `λhead. λtail. λnil. λcons. cons head (tail nil cons)` |
+//!
+//! The eliminator `caseList = λx. λnil. λcons. x nil cons` takes a case for
+//! every constructor, which receives the fields. Under the Church encoding,
+//! it's a fold, i. e. the cases receive the results of recursive fields
+//! instead of the fields themselves.
+use crate::{decode::unshift, Exp};
+
+/// Encoding of data types.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// A value is the case of its constructor applied to the fields,
+    /// supporting constant-time access to the fields
+    #[default]
+    Scott,
+    /// A value is its own fold, supporting iteration without recursion
+    Church,
+}
+
+/// A field of a constructor, like `tail:List`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    /// Name of the field
+    pub name: String,
+    /// Whether the field is of the data type itself
+    pub recursive: bool,
+}
+
+/// A constructor of a data type, like `Cons head tail:List`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constructor {
+    /// Name of the constructor
+    pub name: String,
+    /// Fields in order
+    pub fields: Vec<Field>,
+}
+
+/// A data type declared like `data List = Nil | Cons head tail:List`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataType {
+    /// Name of the type
+    pub name: String,
+    /// Encoding of the values
+    pub encoding: Encoding,
+    /// Constructors in order, which is the order of the cases
+    pub constructors: Vec<Constructor>,
+    /// Name of the eliminator, `caseList` for `List`
+    pub eliminator: String,
+}
+
+/// A value of a data type decoded by [`DataType::decode_value`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Value {
+    /// A constructor applied to fields, where recursive fields are decoded
+    /// too
+    Data {
+        /// Name of the constructor
+        constructor: String,
+        /// Values of the fields
+        fields: Vec<Value>,
+    },
+    /// A field which isn't of the data type
+    Exp(Exp<String>),
+}
+
+/// Name of the case of a constructor, `cons` for `Cons`.
+fn case_name(constructor: &str) -> String {
+    let name = constructor.rsplit('.').next().unwrap_or(constructor);
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) => c.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+impl DataType {
+    /// New data type with the eliminator named after it.
+    pub fn new(
+        name: impl Into<String>,
+        encoding: Encoding,
+        constructors: Vec<Constructor>,
+    ) -> Self {
+        let name = name.into();
+        Self {
+            eliminator: format!("case{}", name),
+            name,
+            encoding,
+            constructors,
+        }
+    }
+    /// The constructor named `name`.
+    pub fn constructor(&self, name: &str) -> Option<&Constructor> {
+        self.constructors.iter().find(|c| c.name == name)
+    }
+    /// Wrap `body` in abstractions of the cases.
+    fn abs_cases(&self, mut body: Exp<String>) -> Exp<String> {
+        for c in self.constructors.iter().rev() {
+            body = Exp::abs(case_name(&c.name), body);
+        }
+        body
+    }
+    /// Definitions of the constructors and the eliminator, in order.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::{data::{Constructor, DataType, Encoding}, lambda};
+    /// let answer = DataType::new(
+    ///     "Answer",
+    ///     Encoding::Scott,
+    ///     ["Yes", "No"]
+    ///         .map(|name| Constructor { name: name.to_string(), fields: vec![] })
+    ///         .to_vec(),
+    /// );
+    /// let defs = answer.definitions();
+    /// assert_eq!(defs[0], (String::from("Yes"), lambda!(yes. no. yes)));
+    /// assert_eq!(defs[2], (String::from("caseAnswer"), lambda!(x. yes. no. x yes no)));
+    /// ```
+    pub fn definitions(&self) -> Vec<(String, Exp<String>)> {
+        let n = self.constructors.len() as u32;
+        let mut defs = vec![];
+        for (i, c) in self.constructors.iter().enumerate() {
+            let k = c.fields.len() as u32;
+            let mut body = Exp::var(case_name(&c.name), n - i as u32);
+            for (j, field) in c.fields.iter().enumerate() {
+                let mut arg = Exp::var(&field.name, n + k - j as u32);
+                if field.recursive && self.encoding == Encoding::Church {
+                    for (l, case) in self.constructors.iter().enumerate() {
+                        arg = Exp::app(arg, Exp::var(case_name(&case.name), n - l as u32));
+                    }
+                }
+                body = Exp::app(body, arg);
+            }
+            let mut exp = self.abs_cases(body);
+            for field in c.fields.iter().rev() {
+                exp = Exp::abs(&field.name, exp);
+            }
+            defs.push((c.name.clone(), exp));
+        }
+
+        let mut body = Exp::var("x", n + 1);
+        for (l, case) in self.constructors.iter().enumerate() {
+            body = Exp::app(body, Exp::var(case_name(&case.name), n - l as u32));
+        }
+        defs.push((self.eliminator.clone(), Exp::abs("x", self.abs_cases(body))));
+        defs
+    }
+    /// Decode a value in normal form into its constructor and fields, return
+    /// `None` if `exp` isn't one.
+    ///
+    /// Under the Church encoding, recursive fields are rebuilt into values
+    /// of the data type.
+    pub fn decode(&self, exp: &Exp<String>) -> Option<(&Constructor, Vec<Exp<String>>)> {
+        let n = self.constructors.len() as u32;
+        let mut binders = vec![];
+        let mut body = exp;
+        for _ in 0..n {
+            let (ident, inner) = body.into_abs()?;
+            binders.push(ident.clone());
+            body = inner;
+        }
+        let mut args = vec![];
+        while let Exp::App(func, arg) = body {
+            args.push(arg.as_ref());
+            body = func;
+        }
+        args.reverse();
+        let code = body.into_ident()?.1;
+        if code == 0 || code > n {
+            return None;
+        }
+        let c = &self.constructors[(n - code) as usize];
+        if c.fields.len() != args.len() {
+            return None;
+        }
+
+        let mut fields = vec![];
+        for (field, arg) in c.fields.iter().zip(args) {
+            if field.recursive && self.encoding == Encoding::Church {
+                let mut exp = arg.clone();
+                for ident in binders.iter().rev() {
+                    exp = Exp::Abs(ident.clone(), Box::new(exp));
+                }
+                fields.push(exp);
+            } else {
+                fields.push(unshift(arg, n, 0)?);
+            }
+        }
+        Some((c, fields))
+    }
+    /// Decode a value in normal form like [`DataType::decode`], decoding
+    /// recursive fields recursively.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::{data::{Value, DataType}, lambda, parser::parse_module};
+    /// let module = parse_module("data List = Nil | Cons head tail:List").unwrap();
+    /// let list = &module.datatypes[0];
+    /// let value = list.decode_value(&lambda!(nil. cons. cons a (nil. cons. nil))).unwrap();
+    /// let Value::Data { constructor, fields } = value else { panic!() };
+    /// assert_eq!(constructor, "Cons");
+    /// assert_eq!(fields[0], Value::Exp(lambda!(a)));
+    /// ```
+    pub fn decode_value(&self, exp: &Exp<String>) -> Option<Value> {
+        let (c, exps) = self.decode(exp)?;
+        let mut fields = vec![];
+        for (field, exp) in c.fields.iter().zip(exps) {
+            if field.recursive {
+                fields.push(self.decode_value(&exp)?);
+            } else {
+                fields.push(Value::Exp(exp));
+            }
+        }
+        Some(Value::Data {
+            constructor: c.name.clone(),
+            fields,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Constructor, DataType, Encoding, Field, Value};
+    use crate::{env::Environment, lambda, numeral, Error};
+
+    fn list(encoding: Encoding) -> DataType {
+        let field = |name: &str, recursive| Field {
+            name: name.to_string(),
+            recursive,
+        };
+        DataType::new(
+            "List",
+            encoding,
+            vec![
+                Constructor {
+                    name: String::from("Nil"),
+                    fields: vec![],
+                },
+                Constructor {
+                    name: String::from("Cons"),
+                    fields: vec![field("head", false), field("tail", true)],
+                },
+            ],
+        )
+    }
+
+    /// Decode a list of numerals.
+    fn to_vec(value: &Value) -> Option<Vec<u64>> {
+        let Value::Data { fields, .. } = value else {
+            return None;
+        };
+        match &fields[..] {
+            [] => Some(vec![]),
+            [Value::Exp(head), tail] => {
+                let mut res = vec![numeral::decode(head, numeral::Encoding::Church)?];
+                res.extend(to_vec(tail)?);
+                Some(res)
+            }
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_definitions() {
+        let defs = list(Encoding::Scott).definitions();
+        let names: Vec<_> = defs.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["Nil", "Cons", "caseList"]);
+        assert_eq!(defs[0].1, lambda!(nil.cons.nil));
+        assert_eq!(defs[1].1, lambda!(head. tail. nil. cons. cons head tail));
+        assert_eq!(defs[2].1, lambda!(x. nil. cons. x nil cons));
+
+        let defs = list(Encoding::Church).definitions();
+        assert_eq!(
+            defs[1].1,
+            lambda!(head. tail. nil. cons. cons head (tail nil cons))
+        );
+    }
+
+    #[test]
+    fn test_decode() -> Result<(), Error> {
+        for encoding in [Encoding::Scott, Encoding::Church] {
+            let list = list(encoding);
+            let env = Environment::from_defs(list.definitions())?;
+            let mut e = lambda!(Cons 1 (Cons 2 Nil));
+            env.simplify(&mut e)?;
+            let value = list.decode_value(&e).unwrap();
+            assert_eq!(to_vec(&value), Some(vec![1, 2]));
+        }
+
+        // the eliminator of Church lists is a fold
+        let env = Environment::from_defs(list(Encoding::Church).definitions())?;
+        let plus = lambda!(n. m. f. x. n f (m f x));
+        let mut e = lambda!(caseList (Cons 1 (Cons 2 Nil)) 0 {plus});
+        env.simplify(&mut e)?;
+        assert_eq!(numeral::decode(&e, numeral::Encoding::Church), Some(3));
+
+        let list = list(Encoding::Scott);
+        assert_eq!(list.decode(&lambda!(nil. cons. cons nil nil)), None);
+        assert_eq!(list.decode(&lambda!(nil.cons.x)), None);
+        let (c, fields) = list.decode(&lambda!(nil. cons. cons (y. y) t)).unwrap();
+        assert_eq!(c.name, "Cons");
+        assert_eq!(fields, [lambda!(y.y), lambda!(t)]);
+        Ok(())
+    }
+}
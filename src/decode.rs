@@ -0,0 +1,318 @@
+//! Decoding normal forms into Rust values, and encoding Rust values back.
+//!
+//! The encodings agree with the [prelude](crate::prelude):
+//!
+//! | value          | encoding                                        |
+//! |----------------|-------------------------------------------------|
+//! | `true`         | `λx. λy. x`                                     |
+//! | `false`        | `λx. λy. y`                                     |
+//! | `(a, b)`       | `λf. f a b`                                     |
+//! | `[]`           | `λnil. λcons. nil`                              |
+//! | `[x, ..xs]`    | `λnil. λcons. cons x xs`, i. e. Scott lists     |
+//! | `None`         | `λnone. λsome. none`                            |
+//! | `Some(x)`      | `λnone. λsome. some x`                          |
+//!
+//! and natural numbers are Church numerals, see [`numeral`] for the others.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{decode::{FromLambda, IntoLambda}, lambda, prelude};
+//! let mut e = lambda!({prelude::get("Map").unwrap()} {prelude::SUCC} {vec![1u64, 2].into_lambda()});
+//! e.simplify(false).unwrap();
+//! assert_eq!(Vec::<u64>::from_lambda(&e), Some(vec![2, 3]));
+//! ```
+use crate::{
+    numeral::{self, Encoding},
+    Exp, Ident,
+};
+
+/// Move `exp` out of `n` abstractions around it, return `None` if it refers
+/// to them.
+pub(crate) fn unshift<T: Clone + Eq>(exp: &Exp<T>, n: u32, depth: u32) -> Option<Exp<T>> {
+    match exp {
+        Exp::Var(Ident(name, code)) => match *code {
+            0 => Some(exp.clone()),
+            code if code <= depth => Some(exp.clone()),
+            code if code <= depth + n => None,
+            code => Some(Exp::Var(Ident(name.clone(), code - n))),
+        },
+        Exp::Abs(ident, body) => Some(Exp::Abs(
+            ident.clone(),
+            Box::new(unshift(body, n, depth + 1)?),
+        )),
+        Exp::App(func, body) => Some(Exp::App(
+            Box::new(unshift(func, n, depth)?),
+            Box::new(unshift(body, n, depth)?),
+        )),
+    }
+}
+
+/// Strip `n` abstractions, return the body.
+pub(crate) fn strip_abs<T: Clone + Eq>(exp: &Exp<T>, n: u32) -> Option<&Exp<T>> {
+    let mut body = exp;
+    for _ in 0..n {
+        body = body.into_abs()?.1;
+    }
+    Some(body)
+}
+
+/// Split `f a1 .. an` into `f` and the arguments.
+pub(crate) fn spine<T: Clone + Eq>(exp: &Exp<T>) -> (&Exp<T>, Vec<&Exp<T>>) {
+    let mut args = vec![];
+    let mut head = exp;
+    while let Exp::App(func, arg) = head {
+        args.push(arg.as_ref());
+        head = func;
+    }
+    args.reverse();
+    (head, args)
+}
+
+impl<T: Clone + Eq> Exp<T> {
+    /// Decode a Church numeral, e. g. `λf. λx. f (f x)` for 2.
+    pub fn as_church_nat(&self) -> Option<u64> {
+        numeral::decode(self, Encoding::Church)
+    }
+    /// Decode a Scott numeral, e. g. `λz. λs. s (λz. λs. z)` for 1.
+    pub fn as_scott_nat(&self) -> Option<u64> {
+        numeral::decode(self, Encoding::Scott)
+    }
+    /// Decode a binary numeral, e. g. `λe. λo. λi. i (λe. λo. λi. e)` for 1.
+    pub fn as_binary_nat(&self) -> Option<u64> {
+        numeral::decode(self, Encoding::Binary)
+    }
+    /// Decode a Church boolean, `λx. λy. x` for `true` and `λx. λy. y` for
+    /// `false`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match strip_abs(self, 2)?.into_ident()?.1 {
+            2 => Some(true),
+            1 => Some(false),
+            _ => None,
+        }
+    }
+    /// Decode a pair `λf. f a b` into `(a, b)`.
+    pub fn as_pair(&self) -> Option<(Exp<T>, Exp<T>)> {
+        let (head, args) = spine(strip_abs(self, 1)?);
+        match (head.into_ident()?.1, &args[..]) {
+            (1, [a, b]) => Some((unshift(a, 1, 0)?, unshift(b, 1, 0)?)),
+            _ => None,
+        }
+    }
+    /// Decode a Scott list, e. g. `λnil. λcons. cons a (λnil. λcons. nil)`
+    /// for `[a]`.
+    pub fn as_list(&self) -> Option<Vec<Exp<T>>> {
+        let mut res = vec![];
+        let mut cur = self;
+        // abstractions of the enclosing lists, which the elements are moved out of
+        let mut shift = 0;
+        loop {
+            shift += 2;
+            let (head, args) = spine(strip_abs(cur, 2)?);
+            match (head.into_ident()?.1, &args[..]) {
+                (2, []) => return Some(res),
+                (1, [x, xs]) => {
+                    res.push(unshift(x, shift, 0)?);
+                    cur = xs;
+                }
+                _ => return None,
+            }
+        }
+    }
+    /// Decode a Church list, i. e. its right fold, e. g.
+    /// `λnil. λcons. cons a (cons b nil)` for `[a, b]`.
+    pub fn as_church_list(&self) -> Option<Vec<Exp<T>>> {
+        let mut res = vec![];
+        let mut cur = strip_abs(self, 2)?;
+        loop {
+            let (head, args) = spine(cur);
+            match (head.into_ident()?.1, &args[..]) {
+                (2, []) => return Some(res),
+                (1, [x, xs]) => {
+                    res.push(unshift(x, 2, 0)?);
+                    cur = xs;
+                }
+                _ => return None,
+            }
+        }
+    }
+    /// Decode a Scott option, `λnone. λsome. none` for `None` and
+    /// `λnone. λsome. some x` for `Some(x)`.
+    pub fn as_option(&self) -> Option<Option<Exp<T>>> {
+        let (head, args) = spine(strip_abs(self, 2)?);
+        match (head.into_ident()?.1, &args[..]) {
+            (2, []) => Some(None),
+            (1, [x]) => Some(Some(unshift(x, 2, 0)?)),
+            _ => None,
+        }
+    }
+}
+
+/// Rust values encoded as lambda expressions.
+pub trait IntoLambda {
+    /// Encode `self` as a lambda expression, which is closed if `self`
+    /// doesn't contain expressions with free variables.
+    fn into_lambda(self) -> Exp<String>;
+}
+
+/// Rust values decoded from lambda expressions in normal form.
+pub trait FromLambda: Sized {
+    /// Decode `exp`, return `None` if it doesn't encode a value of `Self`.
+    fn from_lambda(exp: &Exp<String>) -> Option<Self>;
+}
+
+// Closed expressions can be put under abstractions as they are, for free
+// variables are of index 0 anywhere.
+
+impl IntoLambda for Exp<String> {
+    fn into_lambda(self) -> Exp<String> {
+        self
+    }
+}
+
+impl FromLambda for Exp<String> {
+    fn from_lambda(exp: &Exp<String>) -> Option<Self> {
+        Some(exp.clone())
+    }
+}
+
+impl IntoLambda for bool {
+    fn into_lambda(self) -> Exp<String> {
+        let body = if self {
+            Exp::var("x", 2)
+        } else {
+            Exp::var("y", 1)
+        };
+        Exp::abs("x", Exp::abs("y", body))
+    }
+}
+
+impl FromLambda for bool {
+    fn from_lambda(exp: &Exp<String>) -> Option<Self> {
+        exp.as_bool()
+    }
+}
+
+macro_rules! impl_nat {
+    ($($t:ty),*) => {
+        $(
+            impl IntoLambda for $t {
+                fn into_lambda(self) -> Exp<String> {
+                    numeral::encode(self as u64, Encoding::Church)
+                }
+            }
+            impl FromLambda for $t {
+                fn from_lambda(exp: &Exp<String>) -> Option<Self> {
+                    exp.as_church_nat()?.try_into().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_nat!(u8, u16, u32, u64, usize);
+
+impl<A: IntoLambda, B: IntoLambda> IntoLambda for (A, B) {
+    fn into_lambda(self) -> Exp<String> {
+        let body = Exp::app(
+            Exp::app(Exp::var("f", 1), self.0.into_lambda()),
+            self.1.into_lambda(),
+        );
+        Exp::abs("f", body)
+    }
+}
+
+impl<A: FromLambda, B: FromLambda> FromLambda for (A, B) {
+    fn from_lambda(exp: &Exp<String>) -> Option<Self> {
+        let (a, b) = exp.as_pair()?;
+        Some((A::from_lambda(&a)?, B::from_lambda(&b)?))
+    }
+}
+
+impl<T: IntoLambda> IntoLambda for Vec<T> {
+    fn into_lambda(self) -> Exp<String> {
+        let mut res = Exp::abs("nil", Exp::abs("cons", Exp::var("nil", 2)));
+        for x in self.into_iter().rev() {
+            let body = Exp::app(Exp::app(Exp::var("cons", 1), x.into_lambda()), res);
+            res = Exp::abs("nil", Exp::abs("cons", body));
+        }
+        res
+    }
+}
+
+impl<T: FromLambda> FromLambda for Vec<T> {
+    fn from_lambda(exp: &Exp<String>) -> Option<Self> {
+        exp.as_list()?.iter().map(T::from_lambda).collect()
+    }
+}
+
+impl<T: IntoLambda> IntoLambda for Option<T> {
+    fn into_lambda(self) -> Exp<String> {
+        let body = match self {
+            None => Exp::var("none", 2),
+            Some(x) => Exp::app(Exp::var("some", 1), x.into_lambda()),
+        };
+        Exp::abs("none", Exp::abs("some", body))
+    }
+}
+
+impl<T: FromLambda> FromLambda for Option<T> {
+    fn from_lambda(exp: &Exp<String>) -> Option<Self> {
+        match exp.as_option()? {
+            None => Some(None),
+            Some(x) => T::from_lambda(&x).map(Some),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FromLambda, IntoLambda};
+    use crate::{lambda, numeral, prelude, Error, Exp};
+
+    fn round_trip<T: IntoLambda + FromLambda + Clone + PartialEq + std::fmt::Debug>(value: T) {
+        assert_eq!(T::from_lambda(&value.clone().into_lambda()), Some(value));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        round_trip(true);
+        round_trip(false);
+        round_trip(42u64);
+        round_trip((3u32, true));
+        round_trip(vec![vec![1usize], vec![], vec![2, 3]]);
+        round_trip(vec![Some(1u8), None]);
+        round_trip(lambda!(x.y));
+        assert_eq!(u8::from_lambda(&300u64.into_lambda()), None);
+        assert_eq!(bool::from_lambda(&lambda!(x.y.z)), None);
+    }
+
+    #[test]
+    fn test_decode() -> Result<(), Error> {
+        let env = prelude::environment();
+        let mut e = lambda!(Pair (Succ 1) (Cons True (Cons False Nil)));
+        env.simplify(&mut e)?;
+        assert_eq!(
+            <(u64, Vec<bool>)>::from_lambda(&e),
+            Some((2, vec![true, false]))
+        );
+        assert_eq!(e.as_pair().unwrap().0.as_church_nat(), Some(2));
+
+        // list elements referring to the binders of the list aren't values
+        assert_eq!(lambda!(nil. cons. cons nil nil).as_list(), None);
+        assert_eq!(
+            lambda!(nil. cons. cons a (cons b nil)).as_church_list(),
+            Some(vec![lambda!(a), lambda!(b)])
+        );
+        assert_eq!(
+            numeral::encode(5, numeral::Encoding::Scott).as_scott_nat(),
+            Some(5)
+        );
+        assert_eq!(
+            numeral::encode(6, numeral::Encoding::Binary).as_binary_nat(),
+            Some(6)
+        );
+        assert_eq!(Exp::<String>::from_lambda(&lambda!(a)), Some(lambda!(a)));
+        Ok(())
+    }
+}
@@ -0,0 +1,471 @@
+//! Resolving free variables against named definitions.
+use crate::{builder, lambda, module::Module, Error, Exp, Ident, SIMPLIFY_LIMIT};
+use std::collections::HashMap;
+
+/// When definitions are substituted for the free variables naming them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Expansion {
+    /// Substitute every name before reducing.
+    #[default]
+    Eager,
+    /// Substitute a name only when it's reached by normal order reduction,
+    /// so that definitions in discarded arguments are never expanded.
+    Lazy,
+}
+
+/// How recursive definitions are compiled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Recursion {
+    /// Return [`Error::CyclicDefinition`] for recursive definitions.
+    Reject,
+    /// Rewrite through `Y = λf. (λx. f (x x)) (λx. f (x x))`, for normal order
+    /// reduction.
+    #[default]
+    Y,
+    /// Rewrite through `Z = λf. (λx. f (λv. x x v)) (λx. f (λv. x x v))`, for
+    /// call-by-value reduction.
+    Z,
+}
+
+impl Recursion {
+    /// The fixed-point combinator, if any.
+    pub fn combinator(&self) -> Option<Exp<String>> {
+        match self {
+            Recursion::Reject => None,
+            Recursion::Y => Some(lambda!(f. (x. f (x x)) (x. f (x x)))),
+            Recursion::Z => Some(lambda!(f. (x. f (v. x x v)) (x. f (v. x x v)))),
+        }
+    }
+}
+
+/// Named definitions that free variables (De Bruijn index 0) refer to.
+///
+/// Every free variable of a definition must be defined. Definitions
+/// referring to themselves, directly or not, are rewritten through a
+/// fixed-point combinator, see [`Recursion`]:
+///
+/// - `f = e` for recursive `f` becomes `f = Y (λf. e)`.
+/// - Mutually recursive `f = e1` and `g = e2` become a pair
+///   `f,g = Y (λf,g. λs. s e1' e2')` where `e1'` and `e2'` refer to `f` and
+///   `g` by selecting from `f,g`, and then `f = f,g (λx y. x)` and
+///   `g = f,g (λx y. y)`.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::{env::Environment, lambda, parser::parse_module};
+/// let module = parse_module(
+///     r"
+///     Two = Succ One
+///     One = Succ Zero
+///     Zero = \f x. x
+///     Succ = \n f x. f (n f x)
+///     ",
+/// )
+/// .unwrap();
+/// let env = Environment::from_module(&module).unwrap();
+/// assert_eq!(env.order(), ["Succ", "Zero", "One", "Two"]);
+///
+/// let mut two = lambda!(Two);
+/// env.simplify(&mut two).unwrap();
+/// assert_eq!(two, lambda!(f. x. f (f x)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Environment {
+    /// definitions as written, in the order of definition
+    defs: Vec<(String, Exp<String>)>,
+    /// definitions without recursion, including the tuples of mutually
+    /// recursive ones
+    compiled: HashMap<String, Exp<String>>,
+    order: Vec<String>,
+    recursion: Recursion,
+    /// How [`Environment::simplify`] expands names
+    pub expansion: Expansion,
+}
+
+/// Names of free variables in the order of occurrence, without duplicates.
+fn free_names(exp: &Exp<String>, names: &mut Vec<String>) {
+    match exp {
+        Exp::Var(Ident(name, 0)) => {
+            if !names.contains(name) {
+                names.push(name.clone())
+            }
+        }
+        Exp::Var(_) => {}
+        Exp::Abs(_, body) => free_names(body, names),
+        Exp::App(func, body) => {
+            free_names(func, names);
+            free_names(body, names);
+        }
+    }
+}
+
+/// Strongly connected components of definitions referring to each other,
+/// found by Tarjan's algorithm. Every component comes after the ones it
+/// refers to, and consists of indices in the order of definition.
+fn components(deps: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct State<'a> {
+        deps: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        counter: usize,
+        res: Vec<Vec<usize>>,
+    }
+    fn visit(s: &mut State, v: usize) {
+        s.index[v] = Some(s.counter);
+        s.low[v] = s.counter;
+        s.counter += 1;
+        s.stack.push(v);
+        s.on_stack[v] = true;
+        for &w in s.deps[v].iter() {
+            match s.index[w] {
+                None => {
+                    visit(s, w);
+                    s.low[v] = s.low[v].min(s.low[w]);
+                }
+                Some(i) if s.on_stack[w] => s.low[v] = s.low[v].min(i),
+                Some(_) => {}
+            }
+        }
+        if Some(s.low[v]) == s.index[v] {
+            let mut component = vec![];
+            loop {
+                let w = s.stack.pop().unwrap();
+                s.on_stack[w] = false;
+                component.push(w);
+                if w == v {
+                    break;
+                }
+            }
+            component.sort();
+            s.res.push(component);
+        }
+    }
+    let n = deps.len();
+    let mut s = State {
+        deps,
+        index: vec![None; n],
+        low: vec![0; n],
+        on_stack: vec![false; n],
+        stack: vec![],
+        counter: 0,
+        res: vec![],
+    };
+    for v in 0..n {
+        if s.index[v].is_none() {
+            visit(&mut s, v);
+        }
+    }
+    s.res
+}
+
+/// Selector of the `i`-th of `n` components of a tuple `λs. s x1 .. xn`.
+fn selector(i: usize, n: usize) -> Exp<String> {
+    let mut exp = Exp::Var(Ident(format!("x{}", i + 1), (n - i) as u32));
+    for j in (0..n).rev() {
+        exp = Exp::Abs(Ident(format!("x{}", j + 1), 0), Box::new(exp));
+    }
+    exp
+}
+
+impl Environment {
+    /// Environment of the definitions in effect of a module.
+    pub fn from_module(module: &Module) -> Result<Self, Error> {
+        Self::from_defs(
+            module
+                .visible()
+                .map(|def| (def.name.clone(), def.exp.clone())),
+        )
+    }
+    /// Environment of definitions, where later ones of the same names win.
+    ///
+    /// Return [`Error::UndefinedName`] for definitions referring to undefined
+    /// names.
+    pub fn from_defs(defs: impl IntoIterator<Item = (String, Exp<String>)>) -> Result<Self, Error> {
+        let mut env = Self::default();
+        for (name, exp) in defs {
+            match env.defs.iter_mut().find(|(n, _)| *n == name) {
+                Some(def) => def.1 = exp,
+                None => env.defs.push((name, exp)),
+            }
+        }
+        env.compile()?;
+        Ok(env)
+    }
+    /// Set how recursive definitions are compiled, compiling them again.
+    pub fn with_recursion(mut self, recursion: Recursion) -> Result<Self, Error> {
+        self.recursion = recursion;
+        self.compile()?;
+        Ok(self)
+    }
+    fn compile(&mut self) -> Result<(), Error> {
+        let index: HashMap<&String, usize> = self
+            .defs
+            .iter()
+            .enumerate()
+            .map(|(i, (name, _))| (name, i))
+            .collect();
+        let mut deps = vec![];
+        for (name, exp) in &self.defs {
+            let mut names = vec![];
+            free_names(exp, &mut names);
+            let mut dep = vec![];
+            for n in names {
+                match index.get(&n) {
+                    Some(&i) => dep.push(i),
+                    None => return Err(Error::UndefinedName(n, name.clone())),
+                }
+            }
+            deps.push(dep);
+        }
+
+        self.compiled.clear();
+        self.order.clear();
+        for component in components(&deps) {
+            let names: Vec<&String> = component.iter().map(|&i| &self.defs[i].0).collect();
+            self.order.extend(names.iter().map(|n| n.to_string()));
+            let first = component[0];
+            if component.len() == 1 && !deps[first].contains(&first) {
+                let (name, exp) = &self.defs[first];
+                self.compiled.insert(name.clone(), exp.clone());
+                continue;
+            }
+            let Some(fix) = self.recursion.combinator() else {
+                let mut cycle: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+                cycle.push(names[0].clone());
+                return Err(Error::CyclicDefinition(cycle));
+            };
+            if let [name] = names[..] {
+                let exp = builder::abs(name.clone(), self.defs[first].1.clone());
+                self.compiled
+                    .insert(name.clone(), builder::app(vec![fix, exp]));
+                continue;
+            }
+
+            // refer to the definitions by selecting from the tuple of them
+            let tuple = names
+                .iter()
+                .map(|n| n.as_str())
+                .collect::<Vec<_>>()
+                .join(",");
+            let n = names.len();
+            let select = |i: usize| {
+                builder::app(vec![builder::unbounded_var(tuple.clone()), selector(i, n)])
+            };
+            let mut elements = vec![Exp::Var(Ident(String::from("s"), 1))];
+            for &i in &component {
+                let mut exp = self.defs[i].1.clone();
+                for (j, name) in names.iter().enumerate() {
+                    exp.subst_unbounded(name, &select(j));
+                }
+                elements.push(exp);
+            }
+            // build `λs. s e1' .. en'` by hand, where `s` can't capture any
+            // free variables of the definitions
+            let body = Exp::Abs(
+                Ident(String::from("s"), 0),
+                Box::new(builder::app(elements)),
+            );
+            let exp = builder::abs(tuple.clone(), body);
+            self.compiled
+                .insert(tuple.clone(), builder::app(vec![fix, exp]));
+            for (j, name) in names.iter().enumerate() {
+                self.compiled.insert(name.to_string(), select(j));
+            }
+        }
+        Ok(())
+    }
+    /// Set how [`Environment::simplify`] expands names.
+    pub fn with_expansion(mut self, expansion: Expansion) -> Self {
+        self.expansion = expansion;
+        self
+    }
+    /// Names of definitions sorted topologically, i. e. every definition
+    /// comes after the ones it refers to.
+    pub fn order(&self) -> &[String] {
+        &self.order
+    }
+    /// The definition of `name` as written.
+    pub fn get(&self, name: &str) -> Option<&Exp<String>> {
+        self.defs
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, exp)| exp)
+    }
+    /// The definition of `name` which names are expanded to, with recursion
+    /// rewritten through the fixed-point combinator.
+    pub fn compiled(&self, name: &str) -> Option<&Exp<String>> {
+        self.compiled.get(name)
+    }
+    /// Substitute every free variable naming a definition, recursively.
+    ///
+    /// Other free variables are left unchanged.
+    pub fn expand<'a>(&self, exp: &'a mut Exp<String>) -> &'a mut Exp<String> {
+        exp.for_each_var(|v, _| {
+            if let Exp::Var(Ident(name, 0)) = v {
+                if let Some(def) = self.compiled.get(name) {
+                    let mut def = def.clone();
+                    self.expand(&mut def);
+                    *v = def;
+                }
+            }
+        });
+        exp
+    }
+    /// Like [`Exp::eval_normal_order`], additionally treating free variables
+    /// naming definitions as redexes, which are replaced by the definitions.
+    ///
+    /// return `false` if nothing changes, otherwise `true`.
+    pub fn eval_normal_order(&self, exp: &mut Exp<String>, eta_reduce: bool) -> bool {
+        if let Exp::Var(Ident(name, 0)) = exp {
+            if let Some(def) = self.compiled.get(name) {
+                *exp = def.clone();
+                return true;
+            }
+        }
+        if exp.beta_reduce() {
+            return true;
+        }
+        if eta_reduce && exp.eta_reduce() {
+            return true;
+        }
+        match exp {
+            Exp::Var(_) => false,
+            Exp::Abs(_, body) => self.eval_normal_order(body, eta_reduce),
+            Exp::App(func, body) => {
+                self.eval_normal_order(func, eta_reduce) || self.eval_normal_order(body, eta_reduce)
+            }
+        }
+    }
+    /// Simplify an expression referring to the definitions, expanding them
+    /// eagerly or lazily by [`Environment::expansion`].
+    ///
+    /// At most [`SIMPLIFY_LIMIT`] reductions are performed, counting the
+    /// expansions of names in the lazy mode.
+    pub fn simplify<'a>(&self, exp: &'a mut Exp<String>) -> Result<&'a mut Exp<String>, Error> {
+        match self.expansion {
+            Expansion::Eager => self.expand(exp).simplify(false),
+            Expansion::Lazy => {
+                for _ in 0..SIMPLIFY_LIMIT {
+                    if !self.eval_normal_order(exp, false) {
+                        return Ok(exp);
+                    }
+                }
+                Err(Error::SimplifyLimitExceeded)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Environment, Expansion, Recursion};
+    use crate::{lambda, parser::parse_module, Error};
+
+    const ARITH: &str = r"
+        Exp = \n. \m. n (Mul m) One
+        Mul = \n. \m. \f. \x. n (m f) x
+        One = \f. \x. f x
+        Two = \f. \x. f (f x)
+        Omega = (\x. x x) (\x. x x)
+        K = \x y. x
+    ";
+
+    #[test]
+    fn test_environment() -> Result<(), Error> {
+        let env = Environment::from_module(&parse_module(ARITH)?)?;
+        let order = env.order();
+        let pos = |name| order.iter().position(|n| n == name).unwrap();
+        assert!(pos("Mul") < pos("Exp") && pos("One") < pos("Exp"));
+
+        let four = lambda!(f. x. f (f (f (f x))));
+        for expansion in [Expansion::Eager, Expansion::Lazy] {
+            let env = env.clone().with_expansion(expansion);
+            let mut e = lambda!(Exp Two Two);
+            env.simplify(&mut e)?;
+            assert_eq!(e, four);
+            // free variables not defined are kept
+            let mut e = lambda!(One y);
+            env.simplify(&mut e)?;
+            assert_eq!(e, lambda!(x. y x));
+        }
+
+        // `Omega` is discarded before expanded
+        let env = env.with_expansion(Expansion::Lazy);
+        let mut e = lambda!(K One Omega);
+        env.simplify(&mut e)?;
+        assert_eq!(e, lambda!(f. x. f x));
+        Ok(())
+    }
+
+    #[test]
+    fn test_bad_definitions() -> Result<(), Error> {
+        let module = parse_module("A = B\nB = \\x. C x")?;
+        let Err(Error::UndefinedName(name, def)) = Environment::from_module(&module) else {
+            panic!("expect undefined name")
+        };
+        assert_eq!((name.as_str(), def.as_str()), ("C", "B"));
+
+        let module = parse_module("A = B\nB = \\x. C x\nC = A")?;
+        let env = Environment::from_module(&module)?;
+        let Err(Error::CyclicDefinition(cycle)) = env.with_recursion(Recursion::Reject) else {
+            panic!("expect cyclic definition")
+        };
+        assert_eq!(cycle, ["A", "B", "C", "A"]);
+
+        let module = parse_module("Fix = \\f. f (Fix f)")?;
+        let env = Environment::from_module(&module)?;
+        assert!(env.with_recursion(Recursion::Reject).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_recursion() -> Result<(), Error> {
+        let lambda = format!(
+            r"{}
+            Three = \f x. f (f (f x))
+            True = \x y. x
+            False = \x y. y
+            IsZero = \n. n (\x. False) True
+            Pred = \n f x. n (\g h. h (g f)) (\u. x) (\u. u)
+            Fact = \n. IsZero n One (Mul n (Fact (Pred n)))
+            Even = \n. IsZero n True (Odd (Pred n))
+            Odd = \n. IsZero n False (Even (Pred n))
+            ",
+            ARITH
+        );
+        let env = Environment::from_module(&parse_module(&lambda)?)?;
+        assert_eq!(
+            env.get("Fact"),
+            Some(&lambda!(n. IsZero n One (Mul n (Fact (Pred n)))))
+        );
+        assert_eq!(env.compiled("Even"), Some(&lambda!("Even,Odd"(x1.x2.x1))));
+        let order = env.order();
+        let pos = |name| order.iter().position(|n| n == name).unwrap();
+        assert!(pos("Pred") < pos("Even") && pos("Even") < pos("Odd"));
+
+        for recursion in [Recursion::Y, Recursion::Z] {
+            for expansion in [Expansion::Eager, Expansion::Lazy] {
+                let env = env
+                    .clone()
+                    .with_recursion(recursion)?
+                    .with_expansion(expansion);
+                let mut e = lambda!(Fact Three);
+                env.simplify(&mut e)?;
+                let mut expected = lambda!(Mul Two Three);
+                env.simplify(&mut expected)?;
+                assert_eq!(e, expected);
+
+                let mut e = lambda!(Odd (Mul Two (Mul Two One)));
+                env.simplify(&mut e)?;
+                assert_eq!(e, lambda!(x.y.y));
+                let mut e = lambda!(Even (Mul Two (Mul Two One)));
+                env.simplify(&mut e)?;
+                assert_eq!(e, lambda!(x.y.x));
+            }
+        }
+        Ok(())
+    }
+}
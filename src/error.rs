@@ -1,10 +1,84 @@
+use std::ops::Range;
+
+/// Diagnostic of a parse failure, locating the offending text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+pub struct ParseError {
+    /// Byte range of the offending text in the input
+    pub span: Range<usize>,
+    /// Line number, starting from 1
+    pub line: usize,
+    /// Column number counted in characters, starting from 1
+    pub column: usize,
+    /// Description of the failure
+    pub message: String,
+    /// Tokens that would have been accepted at `span`
+    pub expected: Vec<String>,
+    /// Suggestion on how to fix it
+    pub help: Option<String>,
+}
+
+/// Line number of the byte `offset` of `input`, starting from 1.
+pub(crate) fn line_of(input: &str, offset: usize) -> usize {
+    input[..offset].matches('\n').count() + 1
+}
+
+impl ParseError {
+    /// Create a diagnostic for `span` of `input`, computing its line and column.
+    pub fn new(input: &str, span: Range<usize>, message: String) -> Self {
+        let before = &input[..span.start];
+        let line_start = before.rfind('\n').map(|p| p + 1).unwrap_or(0);
+        Self {
+            line: line_of(input, span.start),
+            column: before[line_start..].chars().count() + 1,
+            span,
+            message,
+            expected: vec![],
+            help: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)?;
+        if let Some(help) = &self.help {
+            write!(f, " (help: {})", help)?;
+        }
+        Ok(())
+    }
+}
+
 /// Error type.
 #[derive(Debug)]
 pub enum Error {
     /// Too many reductions
     SimplifyLimitExceeded,
-    /// Can't parse expression
-    ParseError(String),
+    /// Can't parse expression. Contains at least one diagnostic.
+    ParseError(Vec<ParseError>),
+    /// A definition (the second) refers to an undefined name (the first)
+    UndefinedName(String, String),
+    /// Definitions refer to themselves, e. g. `[A, B, A]` for `A = B` and `B = A`
+    CyclicDefinition(Vec<String>),
+    /// Can't import a file (the first) because of an error in it (the
+    /// second), e. g. a [`ParseError`](Error::ParseError) located in that
+    /// file
+    ImportError(String, Box<Error>),
+    /// Can't read a file for a reason
+    ReadError(String),
+    /// Files import themselves, e. g. `[a, b, a]`
+    CyclicImport(Vec<String>),
+    /// A constant or primitive (the first) has no pure encoding for a
+    /// reason (the second)
+    NoEncoding(String, String),
+    /// A host function (the first) failed for a reason (the second)
+    HostError(String, String),
+    /// Binary lambda calculus is invalid at a bit position (the first) for a
+    /// reason (the second)
+    InvalidBlc(usize, String),
+    /// Part of the output of a program isn't properly encoded, e. g. an
+    /// element of a Lazy K output isn't a Church numeral
+    InvalidOutput(String),
     /// Can't find beta redex. Note that this error is often recovered as we
     /// will try to find anthor beta redex in the current expression.
     #[cfg(feature = "wasm")]
@@ -27,7 +101,30 @@ impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Error::SimplifyLimitExceeded => f.write_str("too many reductions"),
-            Error::ParseError(msg) => write!(f, "error parsing: {}", msg),
+            Error::ParseError(errors) => {
+                f.write_str("error parsing")?;
+                for e in errors {
+                    write!(f, "\n{}", e)?;
+                }
+                Ok(())
+            }
+            Error::UndefinedName(name, def) => {
+                write!(
+                    f,
+                    "undefined name `{}` in the definition of `{}`",
+                    name, def
+                )
+            }
+            Error::CyclicDefinition(cycle) => {
+                write!(f, "cyclic definitions: {}", cycle.join(" -> "))
+            }
+            Error::ImportError(name, e) => write!(f, "can't import \"{}\": {}", name, e),
+            Error::ReadError(reason) => write!(f, "can't read: {}", reason),
+            Error::CyclicImport(cycle) => write!(f, "cyclic imports: {}", cycle.join(" -> ")),
+            Error::NoEncoding(atom, reason) => write!(f, "can't encode `{}`: {}", atom, reason),
+            Error::HostError(name, reason) => write!(f, "`{}` failed: {}", name, reason),
+            Error::InvalidBlc(pos, reason) => write!(f, "invalid BLC at bit {}: {}", pos, reason),
+            Error::InvalidOutput(exp) => write!(f, "invalid output `{}`", exp),
             #[cfg(feature = "wasm")]
             Error::RedexNotFound => write!(f, "redex not found"),
             #[cfg(feature = "wasm")]
@@ -44,4 +141,11 @@ impl std::fmt::Display for Error {
     }
 }
 
-impl std::error::Error for Error {}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::ImportError(_, e) => Some(e.as_ref()),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,410 @@
+//! Front ends of esoteric languages built on combinators, translated into
+//! expressions of [`S`, `K` and `I`](crate::ski::Combinator):
+//!
+//! | language   | syntax                                                    |
+//! |------------|-----------------------------------------------------------|
+//! | Iota       | `i` for `λx. x S K`, and `*` prefixing applications       |
+//! | Jot        | binary digits, where the empty program is `I`, `F0` is `F S K`, and `F1` is `λx. λy. F (x y)` |
+//! | Unlambda   | `s`, `k`, `i`, and `` ` `` prefixing applications         |
+//! | Lazy K     | any of the above, `S`, `K`, `I` and `(` `)` for combinator calculus, and `#` for comments |
+//!
+//! Only the pure subset of Unlambda is supported, i. e. without I/O and
+//! continuations.
+//!
+//! Programs are run by the I/O convention of Lazy K, see [`run`].
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{esolang::{self, Language}, lambda};
+//! let k = esolang::parse("*i*i*ii", Language::Iota).unwrap();
+//! let mut e = lambda!({k} a b);
+//! e.simplify(false).unwrap();
+//! assert_eq!(e, lambda!(a));
+//!
+//! // the empty program echoes its input
+//! let echo = esolang::parse("", Language::LazyK).unwrap();
+//! let mut output = esolang::run(&echo, b"hi");
+//! assert_eq!(output.by_ref().collect::<Result<Vec<_>, _>>().unwrap(), b"hi");
+//! assert_eq!(output.exit_code(), Some(0));
+//! ```
+use crate::{
+    lambda, numeral, parser::join_names, ski::Combinator, Error, EvalOptions, Exp, Ident,
+    ParseError, SIMPLIFY_LIMIT,
+};
+
+/// Esoteric language of a program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// Iota, by Chris Barker
+    Iota,
+    /// Jot, by Chris Barker
+    Jot,
+    /// Unlambda, by David Madore, without side effects
+    Unlambda,
+    /// Lazy K, by Ben Rudiak-Gould
+    LazyK,
+}
+
+fn combinator(c: Combinator<String>) -> Exp<String> {
+    c.to_exp()
+}
+
+fn iota() -> Exp<String> {
+    lambda!(x. x {combinator(Combinator::S)} {combinator(Combinator::K)})
+}
+
+/// Recursive descent parser of all the languages.
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+    language: Language,
+}
+
+impl Parser<'_> {
+    /// Skip whitespaces, and comments in Lazy K.
+    fn skip(&mut self) {
+        loop {
+            let rest = &self.input[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if self.language != Language::LazyK || !trimmed.starts_with('#') {
+                return;
+            }
+            self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+    }
+    fn peek(&mut self) -> Option<char> {
+        self.skip();
+        self.input[self.pos..].chars().next()
+    }
+    /// Consume the next character.
+    fn bump(&mut self) {
+        if let Some(c) = self.peek() {
+            self.pos += c.len_utf8();
+        }
+    }
+    fn error(&mut self, expected: &[&str]) -> Error {
+        let found = self.peek();
+        let end = self.pos + found.map_or(0, char::len_utf8);
+        let found = match found {
+            Some(c) => format!("`{}`", c),
+            None => String::from("end of input"),
+        };
+        let expected: Vec<String> = expected.iter().map(|s| format!("`{}`", s)).collect();
+        let message = match &expected[..] {
+            [] => format!("unexpected {}", found),
+            _ => format!("expected {}, found {}", join_names(&expected), found),
+        };
+        Error::ParseError(vec![ParseError {
+            expected,
+            ..ParseError::new(self.input, self.pos..end, message)
+        }])
+    }
+    fn program(&mut self) -> Result<Exp<String>, Error> {
+        let exp = match self.language {
+            Language::Iota => self.iota()?,
+            Language::Jot => self.jot(),
+            Language::Unlambda => self.unlambda()?,
+            Language::LazyK => self.cc()?,
+        };
+        match self.peek() {
+            None => Ok(exp),
+            Some(_) => Err(self.error(&[])),
+        }
+    }
+    fn iota(&mut self) -> Result<Exp<String>, Error> {
+        match self.peek() {
+            Some('i') => {
+                self.bump();
+                Ok(iota())
+            }
+            Some('*') => {
+                self.bump();
+                let func = self.iota()?;
+                Ok(Exp::app(func, self.iota()?))
+            }
+            _ => Err(self.error(&["i", "*"])),
+        }
+    }
+    /// Consecutive binary digits, possibly none.
+    fn jot(&mut self) -> Exp<String> {
+        let mut exp = combinator(Combinator::I);
+        while let Some(digit @ ('0' | '1')) = self.peek() {
+            self.bump();
+            exp = match digit {
+                '0' => Exp::app(
+                    Exp::app(exp, combinator(Combinator::S)),
+                    combinator(Combinator::K),
+                ),
+                _ => lambda!(x. y. {exp} (x y)),
+            };
+            if self.language == Language::LazyK && !self.input[self.pos..].starts_with(['0', '1']) {
+                break;
+            }
+        }
+        exp
+    }
+    fn unlambda(&mut self) -> Result<Exp<String>, Error> {
+        let exp = match self.peek() {
+            Some('s') => combinator(Combinator::S),
+            Some('k') => combinator(Combinator::K),
+            Some('i') => combinator(Combinator::I),
+            Some('`') => {
+                self.bump();
+                let func = self.unlambda()?;
+                return Ok(Exp::app(func, self.unlambda()?));
+            }
+            _ => return Err(self.error(&["`", "s", "k", "i"])),
+        };
+        self.bump();
+        Ok(exp)
+    }
+    /// Combinator calculus of Lazy K, i. e. expressions applied from left to
+    /// right, where the empty one is `I`.
+    fn cc(&mut self) -> Result<Exp<String>, Error> {
+        let mut exp = None;
+        while self.peek().is_some_and(|c| c != ')') {
+            let arg = self.lazy_k(false)?;
+            exp = Some(match exp {
+                Some(func) => Exp::app(func, arg),
+                None => arg,
+            });
+        }
+        Ok(exp.unwrap_or_else(|| combinator(Combinator::I)))
+    }
+    /// Expression of Lazy K, where `i` is iota in operands of `*`, otherwise
+    /// `I`.
+    fn lazy_k(&mut self, in_iota: bool) -> Result<Exp<String>, Error> {
+        let exp = match self.peek() {
+            Some('i') if in_iota => iota(),
+            Some('i' | 'I') => combinator(Combinator::I),
+            Some('k' | 'K') => combinator(Combinator::K),
+            Some('s' | 'S') => combinator(Combinator::S),
+            Some('0' | '1') => return Ok(self.jot()),
+            Some('`') => {
+                self.bump();
+                let func = self.lazy_k(false)?;
+                return Ok(Exp::app(func, self.lazy_k(false)?));
+            }
+            Some('*') => {
+                self.bump();
+                let func = self.lazy_k(true)?;
+                return Ok(Exp::app(func, self.lazy_k(true)?));
+            }
+            Some('(') => {
+                self.bump();
+                let exp = self.cc()?;
+                if self.peek() != Some(')') {
+                    return Err(self.error(&[")"]));
+                }
+                exp
+            }
+            _ => {
+                let expected = ["S", "K", "I", "0", "1", "`", "*", "("];
+                return Err(self.error(&expected));
+            }
+        };
+        self.bump();
+        Ok(exp)
+    }
+}
+
+/// Parse a program into an expression without free variables.
+///
+/// Whitespaces are ignored, except that they separate Jot programs in
+/// Lazy K.
+pub fn parse(input: &str, language: Language) -> Result<Exp<String>, Error> {
+    Parser {
+        input,
+        pos: 0,
+        language,
+    }
+    .program()
+}
+
+/// Evaluate `exp` by [`Exp::eval_call_by_name`] for at most `limit` steps,
+/// `None` for no limit.
+fn whnf(exp: &mut Exp<String>, limit: Option<u64>) -> Result<(), Error> {
+    let mut steps = 0;
+    while limit.is_none_or(|limit| steps < limit) {
+        steps += 1;
+        if !exp.eval_call_by_name() {
+            return Ok(());
+        }
+    }
+    Err(Error::SimplifyLimitExceeded)
+}
+
+/// Encode bytes as the input of a Lazy K program, i. e. a list of Church
+/// numerals followed by 256 forever, where `[x, ..xs]` is the pair
+/// `λf. f x xs`.
+pub fn encode_input(bytes: &[u8]) -> Exp<String> {
+    let eof = numeral::encode(256, numeral::Encoding::Church);
+    let stream = lambda!(s. f. f {eof} (s s));
+    let end = lambda!({stream} {stream});
+    bytes.iter().rev().fold(end, |list, byte| {
+        let byte = numeral::encode(*byte as u64, numeral::Encoding::Church);
+        lambda!(f. f {byte} {list})
+    })
+}
+
+/// Output of a Lazy K program, decoded lazily.
+///
+/// The output is a list like the input, whose elements are evaluated one by
+/// one into Church numerals by normal order reduction for at most
+/// [`SIMPLIFY_LIMIT`] steps by default, see [`Output::with_limit`]. The list
+/// ends at the first numeral not less than 256, which is the exit code plus
+/// 256.
+#[derive(Debug, Clone)]
+pub struct Output {
+    list: Exp<String>,
+    limit: Option<u64>,
+    exit_code: Option<u64>,
+    failed: bool,
+}
+
+impl Output {
+    /// Set the maximum number of steps evaluating each element, `None` for
+    /// no limit.
+    pub fn with_limit(mut self, limit: Option<u64>) -> Self {
+        self.limit = limit;
+        self
+    }
+    /// Exit code of the program, `None` if it hasn't ended.
+    pub fn exit_code(&self) -> Option<u64> {
+        self.exit_code
+    }
+    fn next_byte(&mut self) -> Result<Option<u8>, Error> {
+        whnf(&mut self.list, self.limit)?;
+        let k = combinator(Combinator::K);
+        let mut head = lambda!({self.list} {k} f x);
+        head.simplify_with(&EvalOptions {
+            limit: self.limit,
+            ..Default::default()
+        })?;
+        // count `f` in `f (f .. x)`
+        let mut n = 0;
+        let mut cur = &head;
+        loop {
+            match cur {
+                Exp::App(func, body) if matches!(&**func, Exp::Var(Ident(f, 0)) if f == "f") => {
+                    n += 1;
+                    cur = body;
+                }
+                Exp::Var(Ident(x, 0)) if x == "x" => break,
+                _ => return Err(Error::InvalidOutput(head.to_string())),
+            }
+        }
+        if n >= 256 {
+            self.exit_code = Some(n - 256);
+            return Ok(None);
+        }
+        let ki = lambda!({k} {combinator(Combinator::I)});
+        self.list = lambda!({self.list} {ki});
+        Ok(Some(n as u8))
+    }
+}
+
+impl Iterator for Output {
+    type Item = Result<u8, Error>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.exit_code.is_some() {
+            return None;
+        }
+        let res = self.next_byte().transpose();
+        self.failed = matches!(res, Some(Err(_)));
+        res
+    }
+}
+
+/// Run a program by the I/O convention of Lazy K, i. e. apply it to the
+/// [input](encode_input), and decode the result as the [output](Output).
+pub fn run(program: &Exp<String>, input: &[u8]) -> Output {
+    Output {
+        list: Exp::app(program.clone(), encode_input(input)),
+        limit: Some(SIMPLIFY_LIMIT as u64),
+        exit_code: None,
+        failed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, run, Language};
+    use crate::{lambda, numeral, Error, Exp, Ident};
+
+    /// Normal form of `exp` applied to `args`.
+    fn apply(exp: &Exp<String>, args: &[&str]) -> Result<Exp<String>, Error> {
+        let mut e = exp.clone();
+        for arg in args {
+            e = lambda!({e} {Exp::Var(Ident(arg.to_string(), 0))});
+        }
+        e.simplify(false)?;
+        Ok(e)
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), Error> {
+        let sources = [
+            ("*i*i*ii", Language::Iota),
+            ("11100", Language::Jot),
+            ("``s`kki", Language::Unlambda),
+            ("(K I) # comment\n `K*ii 11100", Language::LazyK),
+        ];
+        for (source, language) in sources {
+            let exp = parse(source, language)?;
+            assert_eq!(apply(&exp, &["a", "b"])?, lambda!(a), "{}", source);
+        }
+        let s = parse("*i*i*i*ii", Language::Iota)?;
+        assert_eq!(apply(&s, &["a", "b", "c"])?, lambda!(a c (b c)));
+        let s = parse("11111000", Language::Jot)?;
+        assert_eq!(apply(&s, &["a", "b", "c"])?, lambda!(a c (b c)));
+
+        let Err(Error::ParseError(errors)) = parse("*i", Language::Iota) else {
+            panic!("incomplete application");
+        };
+        assert_eq!(errors[0].span, 2..2);
+        assert_eq!(errors[0].expected, ["`i`", "`*`"]);
+        assert!(parse("(S K", Language::LazyK).is_err());
+        assert!(parse("`si)", Language::Unlambda).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_run() -> Result<(), Error> {
+        // the first byte, then exit with 1
+        let exit = numeral::encode(257, numeral::Encoding::Church);
+        let program = lambda!(input. f. f (input (x. y. x)) (f. f {exit} f));
+        let mut output = run(&program, b"ab");
+        assert_eq!(output.by_ref().collect::<Result<Vec<_>, _>>()?, b"a");
+        assert_eq!(output.exit_code(), Some(1));
+
+        // diverging programs fail, and end the output
+        let program = parse("K(S I I (S I I))", Language::LazyK)?;
+        let mut output = run(&program, b"");
+        assert!(matches!(
+            output.next(),
+            Some(Err(Error::SimplifyLimitExceeded))
+        ));
+        assert!(output.next().is_none());
+
+        let program = lambda!(input. f. f (x. y. x) input);
+        let mut output = run(&program, b"");
+        assert!(matches!(output.next(), Some(Err(Error::InvalidOutput(_)))));
+
+        // the input after 100 bytes, which takes more steps than the limit
+        let n = numeral::encode(100, numeral::Encoding::Church);
+        let program = lambda!(input. {n} (l. l (x. xs. xs)) input);
+        let mut input = vec![0; 100];
+        input.extend(b"ab");
+        let mut output = run(&program, &input).with_limit(Some(100));
+        assert!(matches!(
+            output.next(),
+            Some(Err(Error::SimplifyLimitExceeded))
+        ));
+        let output = run(&program, &input).with_limit(None);
+        assert_eq!(output.collect::<Result<Vec<_>, _>>()?, b"ab");
+        Ok(())
+    }
+}
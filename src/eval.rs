@@ -2,6 +2,7 @@
 use std::sync::Mutex;
 
 use crate::{Error, Exp};
+use std::cell::Cell;
 
 /// Profile data (experimental)
 #[cfg(feature = "experimental")]
@@ -9,6 +10,7 @@ pub struct ProfileData {
     pub beta_reduce_counter: u64,
     pub eta_reduce_counter: u64,
     pub eval_fn_counter: u64,
+    pub accelerate_counter: u64,
 }
 
 #[cfg(feature = "experimental")]
@@ -17,6 +19,7 @@ impl ProfileData {
         self.beta_reduce_counter = 0;
         self.eta_reduce_counter = 0;
         self.eval_fn_counter = 0;
+        self.accelerate_counter = 0;
     }
     fn inc_beta_counter(&mut self) {
         self.beta_reduce_counter += 1;
@@ -27,6 +30,9 @@ impl ProfileData {
     fn inc_eval_fn_counter(&mut self) {
         self.eval_fn_counter += 1;
     }
+    fn inc_accelerate_counter(&mut self) {
+        self.accelerate_counter += 1;
+    }
 }
 
 #[cfg(feature = "experimental")]
@@ -34,11 +40,73 @@ pub static GLOBAL_PROFILE: Mutex<ProfileData> = Mutex::new(ProfileData {
     beta_reduce_counter: 0,
     eta_reduce_counter: 0,
     eval_fn_counter: 0,
+    accelerate_counter: 0,
 });
 
 /// maximum number of reductions in a simplification
 pub const SIMPLIFY_LIMIT: i32 = 1 << 10;
 
+/// Statistics of a simplification.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EvalStats {
+    /// Reductions in normal order, including those evaluating the arguments
+    /// of accelerated operations aside, where the rewrite of an operation is
+    /// one
+    pub steps: u64,
+    /// Operations on Church numerals and booleans rewritten to their
+    /// results, including those in arguments evaluated aside
+    pub accelerations: u64,
+}
+
+thread_local! {
+    /// Statistics of the reductions in normal order on this thread so far.
+    static STATS: Cell<EvalStats> = const {
+        Cell::new(EvalStats {
+            steps: 0,
+            accelerations: 0,
+        })
+    };
+}
+
+/// Count a reduction, `accelerated` if it rewrote an operation.
+fn count_step(accelerated: bool) {
+    let stats = STATS.get();
+    STATS.set(EvalStats {
+        steps: stats.steps + 1,
+        accelerations: stats.accelerations + accelerated as u64,
+    });
+}
+
+/// Rewrite of subterms before their reduction in normal order, e. g.
+/// [`Rules`](crate::rules::Rules).
+pub trait Rewrite<T: Clone + Eq> {
+    /// Rewrite `exp`, return `false` if nothing changed.
+    fn rewrite(&self, exp: &mut Exp<T>) -> bool;
+}
+
+/// Options of the reduction in normal order, see
+/// [`Exp::eval_normal_order_with`].
+pub struct EvalOptions<'a, T: Clone + Eq> {
+    /// Rewrite operations on Church numerals and booleans to their results
+    pub optimize: bool,
+    /// Rewrite applied to every subterm before anything else, a redex if it
+    /// changes something
+    pub rewrite: Option<&'a dyn Rewrite<T>>,
+    /// Maximum number of reductions in a simplification, [`SIMPLIFY_LIMIT`]
+    /// by default, `None` for no limit
+    pub limit: Option<u64>,
+}
+
+impl<T: Clone + Eq> Default for EvalOptions<'_, T> {
+    fn default() -> Self {
+        Self {
+            optimize: false,
+            rewrite: None,
+            limit: Some(SIMPLIFY_LIMIT as u64),
+        }
+    }
+}
+
 impl<T> Exp<T>
 where
     T: Clone + Eq,
@@ -46,11 +114,32 @@ where
     /// Simplify repeatedly using beta-reduction in normal order
     /// for at most [`SIMPLIFY_LIMIT`] times.
     pub fn simplify(&mut self, optimize: bool) -> Result<&mut Self, Error> {
+        self.simplify_with_stats(optimize)?;
+        Ok(self)
+    }
+
+    /// [`Exp::simplify`], returning how it went.
+    pub fn simplify_with_stats(&mut self, optimize: bool) -> Result<EvalStats, Error> {
+        self.simplify_with(&EvalOptions {
+            optimize,
+            ..Default::default()
+        })
+    }
+
+    /// [`Exp::simplify`] with `options`, returning how it went.
+    pub fn simplify_with(&mut self, options: &EvalOptions<T>) -> Result<EvalStats, Error> {
         #[cfg(feature = "experimental")]
         GLOBAL_PROFILE.lock().unwrap().reset_counter();
-        for _ in 0..SIMPLIFY_LIMIT {
-            if !self.eval_normal_order(false, optimize) {
-                return Ok(self);
+        let start = STATS.get();
+        let mut reductions = 0;
+        while options.limit.is_none_or(|limit| reductions < limit) {
+            reductions += 1;
+            if !self.eval_normal_order_with(false, options) {
+                let end = STATS.get();
+                return Ok(EvalStats {
+                    steps: end.steps - start.steps,
+                    accelerations: end.accelerations - start.accelerations,
+                });
             }
         }
         Err(Error::SimplifyLimitExceeded)
@@ -60,12 +149,40 @@ where
     /// That is, whenever possible the arguments are substituted into
     /// the body of an abstraction before the arguments are reduced.
     ///
+    /// With `optimize`, operations on Church numerals and booleans like
+    /// `Mul m n` and `IsZero n` are rewritten to their results in one step,
+    /// if their arguments reduce to numerals or booleans within a bounded
+    /// number of steps. Otherwise they are beta-reduced as usual, so the
+    /// normal form is the same as without `optimize`.
+    ///
     /// return `false` if nothing changes, otherwise `true`.
     pub fn eval_normal_order(&mut self, eta_reduce: bool, optimize: bool) -> bool {
+        self.eval_normal_order_with(
+            eta_reduce,
+            &EvalOptions {
+                optimize,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// [`Exp::eval_normal_order`] with `options`, where subterms changed by
+    /// the rewrite of the options are redexes too.
+    pub fn eval_normal_order_with(&mut self, eta_reduce: bool, options: &EvalOptions<T>) -> bool {
         #[cfg(feature = "experimental")]
         GLOBAL_PROFILE.lock().unwrap().inc_eval_fn_counter();
 
-        if optimize {
+        if options.rewrite.is_some_and(|r| r.rewrite(self)) {
+            count_step(false);
+            return true;
+        }
+        if options.optimize {
+            if !eta_reduce && self.is_church_value() {
+                return false;
+            }
+            if self.accelerate(eta_reduce, options) {
+                return true;
+            }
             if self.try_add_opt_1() {
                 return true;
             }
@@ -80,38 +197,170 @@ where
         if self.beta_reduce() {
             #[cfg(feature = "experimental")]
             GLOBAL_PROFILE.lock().unwrap().inc_beta_counter();
+            count_step(false);
             return true;
         }
         if eta_reduce && self.eta_reduce() {
             #[cfg(feature = "experimental")]
             GLOBAL_PROFILE.lock().unwrap().inc_eta_counter();
+            count_step(false);
             return true;
         }
         match self {
             Exp::Var(_) => false,
-            Exp::Abs(_, body) => body.eval_normal_order(eta_reduce, optimize),
+            Exp::Abs(_, body) => body.eval_normal_order_with(eta_reduce, options),
             Exp::App(l, body) => {
-                if l.eval_normal_order(eta_reduce, optimize) {
+                if l.eval_normal_order_with(eta_reduce, options) {
                     true
                 } else {
-                    body.eval_normal_order(eta_reduce, optimize)
+                    body.eval_normal_order_with(eta_reduce, options)
                 }
             }
         }
     }
+
+    /// Reduce the leftmost, outermost redex that is neither inside an
+    /// abstraction nor in an argument, i. e. make one step of call-by-name
+    /// evaluation towards a weak head normal form.
+    ///
+    /// return `false` if `self` is in weak head normal form.
+    pub fn eval_call_by_name(&mut self) -> bool {
+        #[cfg(feature = "experimental")]
+        GLOBAL_PROFILE.lock().unwrap().inc_eval_fn_counter();
+
+        if self.beta_reduce() {
+            #[cfg(feature = "experimental")]
+            GLOBAL_PROFILE.lock().unwrap().inc_beta_counter();
+            return true;
+        }
+        match self {
+            Exp::App(l, _) => l.eval_call_by_name(),
+            _ => false,
+        }
+    }
+
+    /// Make one step of call-by-value evaluation, where the function and
+    /// then the argument of an application are evaluated before an
+    /// abstraction is applied, and abstractions are values not evaluated
+    /// inside.
+    ///
+    /// return `false` if `self` is a value, or stuck on a free variable.
+    pub fn eval_call_by_value(&mut self) -> bool {
+        #[cfg(feature = "experimental")]
+        GLOBAL_PROFILE.lock().unwrap().inc_eval_fn_counter();
+
+        let Exp::App(l, body) = self else {
+            return false;
+        };
+        if l.eval_call_by_value() || body.eval_call_by_value() {
+            return true;
+        }
+        if self.beta_reduce() {
+            #[cfg(feature = "experimental")]
+            GLOBAL_PROFILE.lock().unwrap().inc_beta_counter();
+            return true;
+        }
+        false
+    }
 }
 
 // Church encoding optimization
 mod optimize {
-    use crate::{lambda, Exp};
+    use super::{count_step, EvalOptions, STATS};
+    use crate::{lambda, Exp, Ident, SIMPLIFY_LIMIT};
+    use std::{borrow::Cow, cell::Cell, sync::OnceLock};
+
+    static PURE_ADD: OnceLock<Exp<()>> = OnceLock::new();
+    static PURE_MUL: OnceLock<Exp<()>> = OnceLock::new();
+
+    /// Operations on Church numerals and booleans recognized by the
+    /// accelerator.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Op {
+        Succ,
+        Pred,
+        Add,
+        Sub,
+        Mul,
+        Pow,
+        IsZero,
+        Leq,
+        Eq,
+        Not,
+        And,
+        Or,
+        Xor,
+    }
+
+    impl Op {
+        fn arity(self) -> usize {
+            match self {
+                Op::Succ | Op::Pred | Op::IsZero | Op::Not => 1,
+                _ => 2,
+            }
+        }
+    }
+
+    /// Common definitions of the operations, compared up to alpha-equivalence.
+    static OPS: OnceLock<Vec<(Exp<()>, Op)>> = OnceLock::new();
+
+    fn ops() -> &'static [(Exp<()>, Op)] {
+        OPS.get_or_init(|| {
+            let t = lambda!(x.y.x);
+            let f = lambda!(x.y.y);
+            let succ = lambda!(n. f. x. f (n f x));
+            let pred = lambda!(n. f. x. n (g. h. h (g f)) (u. x) (u. u));
+            let sub = lambda!(m. n. n {pred} m);
+            let is_zero = lambda!(n. n (x. {f}) {t});
+            let leq = lambda!(m. n. {is_zero} ({sub} m n));
+            let not = lambda!(b. b {f} {t});
+            let and = lambda!(p. q. p q p);
+            vec![
+                (lambda!(n. f. x. n f (f x)), Op::Succ),
+                (lambda!(m. n. f. x. m f (n f x)), Op::Add),
+                (lambda!(m. n. m {succ} n), Op::Add),
+                (succ, Op::Succ),
+                (pred, Op::Pred),
+                (sub, Op::Sub),
+                (lambda!(m. n. f. x. m (n f) x), Op::Mul),
+                (lambda!(m. n. f. m (n f)), Op::Mul),
+                (lambda!(m. n. n m), Op::Pow),
+                (is_zero, Op::IsZero),
+                (lambda!(m. n. {and} ({leq} m n) ({leq} n m)), Op::Eq),
+                (leq, Op::Leq),
+                (lambda!(p. q. p ({not} q) q), Op::Xor),
+                (lambda!(p. a. b. p b a), Op::Not),
+                (not, Op::Not),
+                (lambda!(p. q. p q {f}), Op::And),
+                (and, Op::And),
+                (lambda!(p. q. p p q), Op::Or),
+                (lambda!(p. q. p {t} q), Op::Or),
+            ]
+            .into_iter()
+            .map(|(exp, op)| (exp.purify(), op))
+            .collect()
+        })
+    }
 
-    const PURE_MUL: std::cell::OnceCell<Exp<()>> = std::cell::OnceCell::new();
+    thread_local! {
+        /// Reductions left for evaluating arguments aside, shared by nested
+        /// operations, `None` if no argument is being evaluated.
+        static BUDGET: Cell<Option<i32>> = const { Cell::new(None) };
+    }
+
+    fn church_bool<T: Clone + Eq>(value: bool, x: T, y: T) -> Exp<T> {
+        let body = match value {
+            true => Exp::Var(Ident(x.clone(), 2)),
+            false => Exp::Var(Ident(y.clone(), 1)),
+        };
+        Exp::Abs(Ident(x, 0), Box::new(Exp::Abs(Ident(y, 0), Box::new(body))))
+    }
 
     impl<T> Exp<T>
     where
         T: Clone + Eq,
     {
-        fn try_into_church_num(&self) -> Option<(u64, T, T)> {
+        pub(crate) fn try_into_church_num(&self) -> Option<(u64, T, T)> {
             let (f, body) = self.into_abs()?;
             let (x, mut body) = body.into_abs()?;
             let mut val = 0;
@@ -128,7 +377,7 @@ mod optimize {
             }
             Some((val, f.0.clone(), x.0.clone()))
         }
-        fn from_church_num(num: u64, f: T, x: T) -> Self {
+        pub(crate) fn from_church_num(num: u64, f: T, x: T) -> Self {
             let mut cur = Exp::Var(crate::Ident(x.clone(), 1));
             for _ in 0..num {
                 cur = Exp::App(
@@ -140,9 +389,178 @@ mod optimize {
             cur = Exp::Abs(crate::Ident(f, 0), Box::new(cur));
             cur
         }
+        fn try_into_church_bool(&self) -> Option<(bool, T, T)> {
+            let (x, body) = self.into_abs()?;
+            let (y, body) = body.into_abs()?;
+            let value = match body.into_ident()?.1 {
+                2 => true,
+                1 => false,
+                _ => return None,
+            };
+            Some((value, x.0.clone(), y.0.clone()))
+        }
+        /// Names of the first Church boolean in it.
+        fn find_church_bool(&self) -> Option<(T, T)> {
+            if let Some((_, x, y)) = self.try_into_church_bool() {
+                return Some((x, y));
+            }
+            match self {
+                Exp::Var(_) => None,
+                Exp::Abs(_, body) => body.find_church_bool(),
+                Exp::App(func, body) => func.find_church_bool().or_else(|| body.find_church_bool()),
+            }
+        }
+        /// Whether it's equal to `pure` after purifying, without purifying it.
+        fn is_pure(&self, pure: &Exp<()>) -> bool {
+            match (self, pure) {
+                (Exp::Var(Ident(_, code)), Exp::Var(Ident(_, other))) => code == other,
+                (Exp::Abs(_, body), Exp::Abs(_, other)) => body.is_pure(other),
+                (Exp::App(func, body), Exp::App(other_func, other)) => {
+                    func.is_pure(other_func) && body.is_pure(other)
+                }
+                _ => false,
+            }
+        }
+        /// Whether it's a Church numeral or boolean, which is in normal form.
+        pub(crate) fn is_church_value(&self) -> bool {
+            self.try_into_church_num().is_some() || self.try_into_church_bool().is_some()
+        }
+        /// The value accepted by `is_value` it reduces to, evaluating a copy
+        /// of it in normal order for a bounded number of steps.
+        ///
+        /// return `None` if it doesn't reach such a value, e. g. it diverges.
+        fn evaluate_aside(
+            &self,
+            eta_reduce: bool,
+            options: &EvalOptions<T>,
+            is_value: fn(&Self) -> bool,
+        ) -> Option<Cow<'_, Self>> {
+            if is_value(self) {
+                return Some(Cow::Borrowed(self));
+            }
+            let outer = BUDGET.get();
+            let mut budget = outer.unwrap_or(SIMPLIFY_LIMIT);
+            let mut exp = self.clone();
+            while !is_value(&exp) && budget > 0 {
+                BUDGET.set(Some(budget - 1));
+                let changed = exp.eval_normal_order_with(eta_reduce, options);
+                budget = BUDGET.get().unwrap();
+                if !changed {
+                    break;
+                }
+            }
+            BUDGET.set(outer.map(|_| budget));
+            is_value(&exp).then_some(Cow::Owned(exp))
+        }
+        /// Rewrite an operation on Church numerals or booleans, like `Mul 2 3`,
+        /// to its result in one step, return `false` if nothing changed.
+        ///
+        /// Arguments of a recognized operation that aren't numerals or
+        /// booleans yet are evaluated aside. If they don't reach one, the
+        /// operation is left to beta-reduction, so that the normal form is
+        /// the same as without acceleration.
+        pub(crate) fn accelerate(&mut self, eta_reduce: bool, options: &EvalOptions<T>) -> bool {
+            let stats = STATS.get();
+            let changed = self.try_accelerate(eta_reduce, options);
+            if !changed {
+                // reductions of arguments evaluated aside are thrown away
+                STATS.set(stats);
+            }
+            changed
+        }
+        fn try_accelerate(&mut self, eta_reduce: bool, options: &EvalOptions<T>) -> bool {
+            let mut args = vec![];
+            let mut head: &Exp<T> = self;
+            while let Exp::App(func, arg) = head {
+                args.push(arg.as_ref());
+                head = func;
+            }
+            args.reverse();
+            if !matches!(head, Exp::Abs(..)) {
+                return false;
+            }
+            let Some(op) = ops()
+                .iter()
+                .find(|(exp, _)| head.is_pure(exp))
+                .map(|(_, op)| *op)
+            else {
+                return false;
+            };
+            if args.len() != op.arity() {
+                return false;
+            }
+            // booleans resulting from numerals are named after the ones in
+            // the predicate, e. g. `False` in `IsZero`
+            let names = head.find_church_bool();
+
+            let result = match op {
+                Op::Not | Op::And | Op::Or | Op::Xor => {
+                    let is_bool = |exp: &Self| exp.try_into_church_bool().is_some();
+                    let Some(value) = args[0].evaluate_aside(eta_reduce, options, is_bool) else {
+                        return false;
+                    };
+                    let (p, x, y) = value.try_into_church_bool().unwrap();
+                    match op {
+                        Op::Not => church_bool(!p, x, y),
+                        Op::And | Op::Or if p == (op == Op::Or) => church_bool(p, x, y),
+                        Op::And | Op::Or => args[1].clone(),
+                        _ => {
+                            let Some(value) = args[1].evaluate_aside(eta_reduce, options, is_bool)
+                            else {
+                                return false;
+                            };
+                            let (q, _, _) = value.try_into_church_bool().unwrap();
+                            church_bool(p != q, x, y)
+                        }
+                    }
+                }
+                _ => {
+                    let is_num = |exp: &Self| exp.try_into_church_num().is_some();
+                    let mut nums = vec![];
+                    for arg in &args {
+                        let Some(value) = arg.evaluate_aside(eta_reduce, options, is_num) else {
+                            return false;
+                        };
+                        nums.push(value.try_into_church_num().unwrap());
+                    }
+                    let (m, f, x) = nums[0].clone();
+                    let n = nums.get(1).map_or(0, |num| num.0);
+                    let (a, b) = names.unwrap_or((f.clone(), x.clone()));
+                    let num = match op {
+                        Op::Succ => m.checked_add(1),
+                        Op::Pred => Some(m.saturating_sub(1)),
+                        Op::Add => m.checked_add(n),
+                        Op::Sub => Some(m.saturating_sub(n)),
+                        Op::Mul => m.checked_mul(n),
+                        // `0 m` is `λx. x`, not the numeral `1`
+                        Op::Pow if n == 0 => None,
+                        Op::Pow => u32::try_from(n).ok().and_then(|n| m.checked_pow(n)),
+                        Op::IsZero => return self.rewrite(church_bool(m == 0, a, b)),
+                        Op::Leq => return self.rewrite(church_bool(m <= n, a, b)),
+                        Op::Eq => return self.rewrite(church_bool(m == n, a, b)),
+                        _ => unreachable!(),
+                    };
+                    match num {
+                        Some(num) => Self::from_church_num(num, f, x),
+                        None => return false,
+                    }
+                }
+            };
+            self.rewrite(result)
+        }
+        fn rewrite(&mut self, result: Exp<T>) -> bool {
+            #[cfg(feature = "experimental")]
+            super::GLOBAL_PROFILE
+                .lock()
+                .unwrap()
+                .inc_accelerate_counter();
+            count_step(true);
+            *self = result;
+            true
+        }
+
         fn is_add(&self) -> bool {
-            let add = lambda!(n. m. f. x. n f (m f x)).purify();
-            self.purify() == add
+            self.is_pure(PURE_ADD.get_or_init(|| lambda!(n. m. f. x. n f (m f x)).purify()))
         }
         /// Check if the function is `add k`
         fn is_add_k(&self) -> Option<u64> {
@@ -172,9 +590,7 @@ mod optimize {
             None
         }
         fn is_mul(&self) -> bool {
-            let binding = PURE_MUL;
-            let value = binding.get_or_init(|| lambda!(n. m. f. x. n (m f) x).purify());
-            &self.purify() == value
+            self.is_pure(PURE_MUL.get_or_init(|| lambda!(n. m. f. x. n (m f) x).purify()))
         }
     }
     impl<T> Exp<T>
@@ -194,7 +610,6 @@ mod optimize {
                 let (va, f, x) = a.try_into_church_num()?;
                 let m = add.into_abs()?.1.into_abs()?.0 .0.clone();
 
-                eprintln!("add opt 1: va = {va}");
                 // construct add a
                 let mut cur = Exp::App(
                     Box::new(Exp::Var(crate::Ident(m.clone(), 3))),
@@ -214,7 +629,7 @@ mod optimize {
                 cur = Exp::Abs(crate::Ident(f, 0), Box::new(cur));
                 cur = Exp::Abs(crate::Ident(m, 0), Box::new(cur));
 
-                *self = cur;
+                self.rewrite(cur);
                 Some(())
             };
             inner().is_some()
@@ -227,12 +642,9 @@ mod optimize {
             let mut inner = || {
                 let (add_a, b) = self.into_app_mut()?;
                 let vb = b.try_into_church_num()?;
-                // eprintln!("try add opt: match b = {}", vb.0);
-                // eprintln!("try add opt: add_a = {}", add_a.purify());
                 let va = add_a.is_add_k()?;
-                eprintln!("add opt: va = {va}, vb = {}", vb.0);
                 let s = Self::from_church_num(va + vb.0, vb.1, vb.2);
-                *self = s;
+                self.rewrite(s);
                 Some(())
             };
             inner().is_some()
@@ -250,7 +662,7 @@ mod optimize {
                 }
                 let va = a.try_into_church_num()?;
                 let s = Self::from_church_num(va.0 * vb.0, va.1, va.2);
-                *self = s;
+                self.rewrite(s);
                 Some(())
             };
             inner().is_some()
@@ -259,7 +671,7 @@ mod optimize {
 
     #[cfg(test)]
     mod tests {
-        use crate::{lambda, Exp};
+        use crate::{lambda, prelude, Exp};
 
         #[test]
         fn test_church() {
@@ -288,7 +700,7 @@ mod optimize {
             for i in 0..5 {
                 let mut e = lambda!({add} {Exp::from_church_num(i, "f".into(), "x".into())});
                 e.simplify(true).unwrap();
-                eprintln!("{}", e);
+                assert!(e.into_abs().is_some());
             }
         }
         #[test]
@@ -296,19 +708,113 @@ mod optimize {
             let a = Exp::from_church_num(10, "f", "x").to_string_exp();
             let b = Exp::from_church_num(15, "f", "x").to_string_exp();
             let add = lambda!(n. m. f. x. n f (m f x));
-            // let add_a = lambda!({add} {a}).simplify(false).unwrap().to_owned();
             let mut e = lambda!({add} {a} {b});
-            eprintln!("{}", e);
+            assert!(e.eval_normal_order(false, true));
+            assert!(!e.eval_normal_order(false, true));
+            assert_eq!(e.try_into_church_num().unwrap().0, 25)
+        }
+        #[test]
+        fn test_accelerate() {
+            let get = |name| prelude::get(name).unwrap();
+            let num = |n| Exp::from_church_num(n, String::from("f"), String::from("x"));
+            let (t, f) = (lambda!(x.y.x), lambda!(x.y.y));
+            let cases = [
+                (lambda!({get("Pred")} {num(7)}), num(6)),
+                (lambda!({get("Sub")} {num(3)} {num(7)}), num(0)),
+                (lambda!({get("Pow")} {num(3)} {num(4)}), num(81)),
+                (lambda!({get("Mul")} {num(30)} {num(40)}), num(1200)),
+                (lambda!({get("IsZero")} {num(0)}), t.clone()),
+                (lambda!({get("Leq")} {num(40)} {num(30)}), f.clone()),
+                (lambda!({get("Eq")} {num(40)} {num(40)}), t.clone()),
+                (lambda!({get("Not")} {t.clone()}), f.clone()),
+                (lambda!({get("And")} {t.clone()} {f.clone()}), f.clone()),
+                (lambda!({get("Or")} {t.clone()} a), t.clone()),
+                (lambda!({get("Xor")} {t.clone()} {t.clone()}), f.clone()),
+            ];
+            for (mut e, res) in cases {
+                assert!(e.eval_normal_order(false, true), "{}", e);
+                assert_eq!(e, res);
+            }
+
+            // arguments are evaluated aside, rewriting in one step
+            let mut e = lambda!({get("Mul")} ({get("Succ")} {num(9)}) ({get("Pred")} {num(3)}));
+            let mut steps = 0;
             while e.eval_normal_order(false, true) {
-                eprintln!("{}", e);
+                steps += 1;
             }
-            assert_eq!(e.try_into_church_num().unwrap().0, 25)
+            assert_eq!((e.try_into_church_num().unwrap().0, steps), (20, 1));
+            let mut e = lambda!({get("Mul")} ({get("Succ")} {num(9)}) ({get("Pred")} {num(3)}));
+            let stats = e.simplify_with_stats(true).unwrap();
+            // the rewrites of the arguments count as steps too
+            assert_eq!((stats.steps, stats.accelerations), (3, 3));
+            let mut e = lambda!({get("Mul")} ((x. x) {num(9)}) {num(2)});
+            let stats = e.simplify_with_stats(true).unwrap();
+            assert_eq!((stats.steps, stats.accelerations), (2, 1));
+
+            // the normal form is the same as without acceleration, even if
+            // an argument diverges, or the result isn't a numeral
+            let omega = lambda!((x. x x) (x. x x));
+            let cases = [
+                lambda!((m. n. n m) {omega.clone()} (u. y)),
+                lambda!({get("Mul")} {num(0)} {omega.clone()}),
+                lambda!({get("And")} {get("False")} {omega.clone()}),
+                lambda!({get("Pow")} {num(2)} {num(0)}),
+            ];
+            for e in cases {
+                let mut plain = e.clone();
+                plain.simplify(false).unwrap();
+                let mut accelerated = e;
+                accelerated.simplify(true).unwrap();
+                assert_eq!(accelerated, plain);
+            }
+
+            let mut e = lambda!((m. n. n m) {omega} (u. y));
+            let stats = e.simplify_with_stats(true).unwrap();
+            assert_eq!((e, stats.accelerations), (lambda!(y), 0));
+
+            // operations on other expressions are reduced as usual
+            let mut e = lambda!({get("Mul")} a b);
+            e.simplify(true).unwrap();
+            assert_eq!(e, lambda!(f. a (b f)));
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[test]
+    fn test_call_by_name() {
+        use crate::lambda;
+
+        // arguments are substituted unevaluated, bodies aren't evaluated
+        let omega = lambda!((x. x x) (x. x x));
+        let mut e = lambda!((x. y. (z. z) x) {omega});
+        assert!(e.eval_call_by_name());
+        assert_eq!(e, lambda!(y. (z. z) {omega}));
+        assert!(!e.eval_call_by_name());
+
+        let mut e = lambda!(a ((x. x) b));
+        assert!(!e.eval_call_by_name());
+    }
+
+    #[test]
+    fn test_call_by_value() {
+        use crate::lambda;
+
+        // arguments are evaluated before substitution
+        let mut e = lambda!((x. y. x) ((z. z) a));
+        assert!(e.eval_call_by_value());
+        assert_eq!(e, lambda!((x. y. x) a));
+        assert!(e.eval_call_by_value());
+        assert_eq!(e, lambda!(y.a));
+        assert!(!e.eval_call_by_value());
+
+        let omega = lambda!((x. x x) (x. x x));
+        let mut e = lambda!((x. y. y) {omega});
+        assert!(e.eval_call_by_value());
+        assert_eq!(e, lambda!((x. y. y) {omega}));
+    }
+
     #[test]
     #[cfg(feature = "experimental")]
     fn bench_pred() -> Result<(), crate::Error> {
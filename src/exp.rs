@@ -1,4 +1,4 @@
-use std::fmt::Write;
+use crate::pretty::PrintOptions;
 
 /// Identifier of variables.
 ///
@@ -8,6 +8,21 @@ use std::fmt::Write;
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Ident<T: Clone + Eq>(pub T, pub u32);
 
+/// Step from an expression to one of its direct subexpressions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Branch {
+    /// Body of an abstraction
+    AbsBody,
+    /// Function of an application
+    AppFunc,
+    /// Body of an application
+    AppBody,
+}
+
+/// Location of a subexpression, as steps from the root expression.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Path(pub Vec<Branch>);
+
 /// Expression in Lambda Calculus.
 ///
 /// T represents the type of indentifiers.
@@ -28,6 +43,43 @@ pub enum Exp<T: Clone + Eq> {
     App(Box<Exp<T>>, Box<Exp<T>>),
 }
 
+impl<T: Clone + Eq> Drop for Exp<T> {
+    // Church numerals of large numbers are millions of applications deep,
+    // so subexpressions are detached onto a stack instead of dropped
+    // recursively, leaving a variable in their place.
+    fn drop(&mut self) {
+        let mut stack = vec![];
+        let mut ident = None;
+        let mut detach = |exp: &mut Exp<T>, stack: &mut Vec<Exp<T>>| {
+            let children = match exp {
+                Exp::Var(_) => return,
+                Exp::Abs(_, body) => [Some(body), None],
+                Exp::App(func, body) => [Some(func), Some(body)],
+            };
+            for child in children.into_iter().flatten() {
+                if matches!(**child, Exp::Var(_)) {
+                    continue;
+                }
+                let ident = ident.get_or_insert_with(|| {
+                    let mut leaf: &Exp<T> = child;
+                    loop {
+                        match leaf {
+                            Exp::Var(ident) => break ident.clone(),
+                            Exp::Abs(_, body) => leaf = body,
+                            Exp::App(func, _) => leaf = func,
+                        }
+                    }
+                });
+                stack.push(std::mem::replace(&mut **child, Exp::Var(ident.clone())));
+            }
+        };
+        detach(self, &mut stack);
+        while let Some(mut exp) = stack.pop() {
+            detach(&mut exp, &mut stack);
+        }
+    }
+}
+
 impl<T: Clone + Eq> Exp<T> {
     // iterate over each variable
     fn reduce_by_var_with_depth<F, D>(&mut self, f: F, depth: u32, sum: D) -> D
@@ -100,7 +152,7 @@ impl<T: Clone + Eq> Exp<T> {
     /// Alter the de bruijn index of outer captured variable
     /// by a shift. It's often used in applying an abstraction
     /// to anthor abstraction.
-    fn shift_outer_captured_var(&mut self, shift: isize) {
+    pub(crate) fn shift_outer_captured_var(&mut self, shift: isize) {
         self.for_each_var(|v, dep| {
             let ident = v.into_ident_mut().unwrap();
             if ident.1 > dep {
@@ -197,6 +249,34 @@ impl<T: Clone + Eq> Exp<T> {
             Exp::App(func, body) => Exp::App(Box::new(func.purify()), Box::new(body.purify())),
         }
     }
+    /// Number of variables and abstractions, comparable with
+    /// [`Combinator::size`](crate::ski::Combinator::size).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::lambda;
+    /// assert_eq!(lambda!(f. x. f (f x)).size(), 5);
+    /// ```
+    pub fn size(&self) -> usize {
+        match self {
+            Exp::Var(_) => 1,
+            Exp::Abs(_, body) => 1 + body.size(),
+            Exp::App(func, body) => func.size() + body.size(),
+        }
+    }
+    /// Variable of `name` with the code `code`, 0 for free variables.
+    pub(crate) fn var(name: impl Into<T>, code: u32) -> Self {
+        Exp::Var(Ident(name.into(), code))
+    }
+    /// Abstraction of `name` over `body`.
+    pub(crate) fn abs(name: impl Into<T>, body: Self) -> Self {
+        Exp::Abs(Ident(name.into(), 0), Box::new(body))
+    }
+    /// Application of `func` to `body`.
+    pub(crate) fn app(func: Self, body: Self) -> Self {
+        Exp::App(Box::new(func), Box::new(body))
+    }
     /// return func and body for App.
     pub fn into_app(&self) -> Option<(&Self, &Self)> {
         match self {
@@ -239,6 +319,22 @@ impl<T: Clone + Eq> Exp<T> {
             _ => None,
         }
     }
+    /// return the subexpression at `path`.
+    pub fn get(&self, path: &Path) -> Option<&Self> {
+        path.0.iter().try_fold(self, |exp, branch| match branch {
+            Branch::AbsBody => exp.into_abs().map(|(_, body)| body),
+            Branch::AppFunc => exp.into_app().map(|(func, _)| func),
+            Branch::AppBody => exp.into_app().map(|(_, body)| body),
+        })
+    }
+    /// return the mutable subexpression at `path`.
+    pub fn get_mut(&mut self, path: &Path) -> Option<&mut Self> {
+        path.0.iter().try_fold(self, |exp, branch| match branch {
+            Branch::AbsBody => exp.into_abs_mut().map(|(_, body)| body),
+            Branch::AppFunc => exp.into_app_mut().map(|(func, _)| func),
+            Branch::AppBody => exp.into_app_mut().map(|(_, body)| body),
+        })
+    }
 }
 
 impl<T> Exp<T>
@@ -261,57 +357,124 @@ where
     }
 }
 
-impl std::fmt::Display for Exp<String> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Canonical name for the `n`-th binder: `x`, `y`, `z`, `x1`, `y1`, `z1`, `x2`...
+pub(crate) fn canonical_name(n: usize) -> String {
+    let base = ["x", "y", "z"][n % 3];
+    match n / 3 {
+        0 => base.to_string(),
+        k => format!("{}{}", base, k),
+    }
+}
+
+impl Exp<()> {
+    fn to_named_exp_with_depth(&self, depth: u32) -> Exp<String> {
         match self {
-            Exp::Var(ident) => {
-                if f.alternate() {
-                    write!(f, "{}<{}>", ident.0, ident.1)
-                } else {
-                    f.write_str(&ident.0.to_string())
-                }
+            Exp::Var(Ident(_, 0)) => Exp::Var(Ident(String::from("_"), 0)),
+            Exp::Var(Ident(_, code)) => {
+                Exp::Var(Ident(canonical_name((depth - code) as usize), *code))
             }
-            Exp::Abs(ident, exp) => {
-                write!(f, "λ{}. ", ident.0)?;
+            Exp::Abs(Ident(_, code), body) => Exp::Abs(
+                Ident(canonical_name(depth as usize), *code),
+                Box::new(body.to_named_exp_with_depth(depth + 1)),
+            ),
+            Exp::App(func, body) => Exp::App(
+                Box::new(func.to_named_exp_with_depth(depth)),
+                Box::new(body.to_named_exp_with_depth(depth)),
+            ),
+        }
+    }
+    /// Invent canonical names for a nameless expression.
+    ///
+    /// The binder nested in `n` abstractions is named after the `n`-th item of
+    /// `x`, `y`, `z`, `x1`, `y1`, `z1`, `x2`..., so no binder shadows another.
+    /// Free variables are named `_`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::parser::parse_de_bruijn;
+    /// let e = parse_de_bruijn("λλλ[2]([[3](2)](1))").unwrap();
+    /// assert_eq!(e.to_named_exp().to_string(), "λx. λy. λz. y ((x y) z)");
+    /// ```
+    pub fn to_named_exp(&self) -> Exp<String> {
+        self.to_named_exp_with_depth(0)
+    }
+}
 
-                if f.alternate() {
-                    write!(f, "{:#}", exp)
-                } else {
-                    write!(f, "{}", exp)
+impl Exp<String> {
+    /// Collect names of the variables referring outside of `depth` abstractions
+    /// (free variables included), according to the names in `scope`.
+    fn collect_outer_names(&self, depth: u32, scope: &[String], names: &mut Vec<String>) {
+        match self {
+            Exp::Var(Ident(name, 0)) => names.push(name.clone()),
+            Exp::Var(Ident(_, code)) => {
+                if *code > depth {
+                    names.push(scope[scope.len() - (code - depth) as usize].clone())
                 }
             }
-            Exp::App(l, exp) => {
-                // 如果 l 是 lambda 那么要加括号
-                let l_str = if f.alternate() {
-                    format!("{:#}", l)
-                } else {
-                    format!("{}", l)
-                };
-                let exp_str = if f.alternate() {
-                    format!("{:#}", exp)
-                } else {
-                    format!("{}", exp)
-                };
-                match **l {
-                    Exp::Var(_) => f.write_str(&l_str),
-                    _ => {
-                        f.write_char('(')?;
-                        f.write_str(&l_str)?;
-                        f.write_char(')')
-                    }
-                }?;
-                f.write_char(' ')?;
-                match **exp {
-                    Exp::App(_, _) => {
-                        f.write_char('(')?;
-                        f.write_str(&exp_str)?;
-                        f.write_char(')')
-                    }
-                    _ => f.write_str(&exp_str),
+            Exp::Abs(_, body) => body.collect_outer_names(depth + 1, scope, names),
+            Exp::App(func, body) => {
+                func.collect_outer_names(depth, scope, names);
+                body.collect_outer_names(depth, scope, names);
+            }
+        }
+    }
+    fn rename_with_scope(&mut self, scope: &mut Vec<String>) {
+        match self {
+            Exp::Var(Ident(name, code)) => {
+                if *code > 0 {
+                    *name = scope[scope.len() - *code as usize].clone();
                 }
             }
+            Exp::Abs(ident, body) => {
+                let mut used = vec![];
+                body.collect_outer_names(1, scope, &mut used);
+                if used.contains(&ident.0) {
+                    let base = ident.0.trim_end_matches(|c: char| c.is_ascii_digit());
+                    let base = if base.is_empty() { "x" } else { base };
+                    ident.0 = (1..)
+                        .map(|k| format!("{}{}", base, k))
+                        .find(|name| !used.contains(name))
+                        .unwrap();
+                }
+                scope.push(ident.0.clone());
+                body.rename_with_scope(scope);
+                scope.pop();
+            }
+            Exp::App(func, body) => {
+                func.rename_with_scope(scope);
+                body.rename_with_scope(scope);
+            }
         }
     }
+    /// Alpha-rename binders so that every variable displays the name of the
+    /// binder it refers to, i. e. the named form means the same as the
+    /// De Bruijn form.
+    ///
+    /// Beta reduction keeps the original names, which may make a variable
+    /// look captured by the wrong abstraction. A binder is renamed only if
+    /// its name is used by a variable in its body referring outside.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::lambda;
+    /// let mut e = lambda!(x. (y. x. y) x);
+    /// e.simplify(false).unwrap();
+    /// assert_eq!(format!("{:#}", e), "λx. λx. x<2>");
+    /// e.rename_for_display();
+    /// assert_eq!(format!("{:#}", e), "λx. λx1. x<2>");
+    /// ```
+    pub fn rename_for_display(&mut self) -> &mut Self {
+        self.rename_with_scope(&mut vec![]);
+        self
+    }
+}
+
+impl std::fmt::Display for Exp<String> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.display_with(&PrintOptions::default()).fmt(f)
+    }
 }
 
 impl std::fmt::Display for Exp<()> {
@@ -375,10 +538,47 @@ mod tests {
         assert_eq!(pair.purify().to_string(), "λλλ[[1](3)](2)");
     }
     #[test]
+    fn test_to_named_exp() {
+        let e = lambda!(a. b. c. d. e. a (d e) c);
+        let named = e.purify().to_named_exp();
+        assert_eq!(named.to_string(), "λx. λy. λz. λx1. λy1. (x (x1 y1)) z");
+        assert_eq!(named.purify(), e.purify());
+
+        let free = lambda!(x. f x).purify().to_named_exp();
+        assert_eq!(format!("{:#}", free), "λx. _<0> x<1>");
+    }
+    #[test]
+    fn test_rename_for_display() -> Result<(), Error> {
+        // (λf. λx. f x) λy. x: the free `x` must not be captured
+        let mut e = lambda!((f. x. f x) (y. x));
+        e.simplify(false)?;
+        assert_eq!(e.to_string(), "λx. x");
+        e.rename_for_display();
+        assert_eq!(format!("{:#}", e), "λx1. x<0>");
+
+        let mut e = lambda!(x. (z. x. z) x);
+        e.simplify(false)?;
+        assert_eq!(format!("{:#}", e), "λx. λx. x<2>");
+        e.rename_for_display();
+        assert_eq!(format!("{:#}", e), "λx. λx1. x<2>");
+
+        let mut e = lambda!(x. y. x y);
+        e.rename_for_display();
+        assert_eq!(e, lambda!(x. y. x y));
+        Ok(())
+    }
+    #[test]
     fn test_beta_reduce() {
         let mut exp = lambda!((x. y. x) z);
         assert!(exp.is_beta_redex());
         assert!(exp.beta_reduce());
         assert_eq!(exp, lambda!(y.z));
     }
+
+    #[test]
+    fn test_drop_deep() {
+        // recursively dropping it overflows the stack of the test thread
+        let exp = crate::numeral::encode(1 << 18, crate::numeral::Encoding::Church);
+        drop(exp);
+    }
 }
@@ -0,0 +1,227 @@
+//! Rust closures embedded as primitives of [`ExpExt`].
+//!
+//! A [`Host`] registers closures under names, which free variables of the
+//! same names call once applied to enough arguments. As the closures may have
+//! side effects, [`Host::eval`] evaluates by call-by-value in a fixed order:
+//!
+//! 1. Abstractions are values, whose bodies aren't evaluated.
+//! 2. In an application, the function is evaluated before the arguments,
+//!    which are evaluated from left to right.
+//! 3. An abstraction is applied once its argument is evaluated, and so are
+//!    [primitives](crate::applied::Prim) and host functions once all their
+//!    arguments are, except for `if`, whose branches are evaluated only
+//!    after the one taken is selected.
+//!
+//! Host functions of no arguments are called whenever their names are
+//! evaluated.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{applied::{self, Const, ExpExt}, host::Host};
+//! let mut log = vec![];
+//! let mut input = vec![1, 2].into_iter();
+//! let mut host = Host::default();
+//! host.register("print", 1, |args| {
+//!     log.push(args[0].to_string());
+//!     Ok(args[0].clone())
+//! });
+//! host.register("read", 0, move |_| match input.next() {
+//!     Some(n) => Ok(ExpExt::from(Const::Int(n))),
+//!     None => Err(String::from("end of input")),
+//! });
+//!
+//! let mut e = applied::parse(r#"(\x y. print (x + y)) read (print read)"#).unwrap();
+//! host.eval(&mut e).unwrap();
+//! assert_eq!(e.as_const(), Some(&Const::Int(3)));
+//! drop(host);
+//! assert_eq!(log, ["2", "3"]);
+//! ```
+use crate::{
+    applied::{Atom, ExpExt, Prim},
+    Error, Exp, Ident, SIMPLIFY_LIMIT,
+};
+use std::collections::HashMap;
+
+/// A Rust closure called with evaluated arguments, returning the result or
+/// why it failed.
+pub type HostFn<'a> = Box<dyn FnMut(&[ExpExt]) -> Result<ExpExt, String> + 'a>;
+
+/// Host functions registered by names.
+#[derive(Default)]
+pub struct Host<'a> {
+    functions: HashMap<String, (usize, HostFn<'a>)>,
+}
+
+impl std::fmt::Debug for Host<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut names: Vec<_> = self.functions.keys().collect();
+        names.sort();
+        f.debug_struct("Host").field("functions", &names).finish()
+    }
+}
+
+/// Split `exp` into the head and the number of arguments applied to it.
+fn spine(exp: &ExpExt) -> (&ExpExt, usize) {
+    let mut head = exp;
+    let mut n = 0;
+    while let Exp::App(func, _) = head {
+        head = func;
+        n += 1;
+    }
+    (head, n)
+}
+
+/// The application of the head to the first `n` of its `len` arguments.
+fn prefix(exp: &mut ExpExt, n: usize, len: usize) -> &mut ExpExt {
+    let mut cur = exp;
+    for _ in n..len {
+        let Exp::App(func, _) = cur else {
+            unreachable!("fewer arguments than counted")
+        };
+        cur = func;
+    }
+    cur
+}
+
+/// The `i`-th of the `len` arguments.
+fn arg(exp: &mut ExpExt, i: usize, len: usize) -> &mut ExpExt {
+    match prefix(exp, i + 1, len) {
+        Exp::App(_, arg) => arg,
+        _ => unreachable!("fewer arguments than counted"),
+    }
+}
+
+impl<'a> Host<'a> {
+    /// Register `f` taking `arity` arguments under `name`, replacing the
+    /// function of the same name.
+    ///
+    /// Names of constants and primitives, like `42` and `+`, aren't
+    /// variables and never refer to host functions.
+    pub fn register(
+        &mut self,
+        name: impl Into<String>,
+        arity: usize,
+        f: impl FnMut(&[ExpExt]) -> Result<ExpExt, String> + 'a,
+    ) {
+        self.functions.insert(name.into(), (arity, Box::new(f)));
+    }
+    /// Arity of the primitive or host function at the head of an
+    /// application.
+    fn arity(&self, head: &ExpExt) -> Option<usize> {
+        match head {
+            Exp::Var(Ident(Atom::Prim(prim), 0)) => Some(prim.arity()),
+            Exp::Var(Ident(Atom::Name(name), 0)) => self.functions.get(name).map(|(n, _)| *n),
+            _ => None,
+        }
+    }
+    /// Make one step of call-by-value evaluation, return `false` if `exp` is
+    /// a value or stuck.
+    ///
+    /// Return [`Error::HostError`] if a host function fails.
+    pub fn step(&mut self, exp: &mut ExpExt) -> Result<bool, Error> {
+        let (head, len) = spine(exp);
+        if let Exp::Abs(..) = head {
+            if len == 0 {
+                return Ok(false);
+            }
+            if self.step(arg(exp, 0, len))? {
+                return Ok(true);
+            }
+            return Ok(prefix(exp, 1, len).beta_reduce());
+        }
+
+        let arity = self.arity(head);
+        // only the condition of `if` is evaluated before selecting a branch
+        let strict = match head {
+            Exp::Var(Ident(Atom::Prim(Prim::If), 0)) => 1,
+            _ => len,
+        };
+        for i in 0..strict.min(len) {
+            if self.step(arg(exp, i, len))? {
+                return Ok(true);
+            }
+        }
+        match arity {
+            Some(arity) if arity <= len => self.call(prefix(exp, arity, len)),
+            _ => Ok(false),
+        }
+    }
+    /// Apply the primitive or host function at the head of `exp` to all its
+    /// arguments.
+    fn call(&mut self, exp: &mut ExpExt) -> Result<bool, Error> {
+        let (head, len) = spine(exp);
+        let Exp::Var(Ident(Atom::Name(name), 0)) = head else {
+            return Ok(exp.delta_reduce());
+        };
+        let name = name.clone();
+        let mut args = Vec::with_capacity(len);
+        let mut cur: &ExpExt = exp;
+        while let Exp::App(func, arg) = cur {
+            args.push(arg.as_ref().clone());
+            cur = func;
+        }
+        args.reverse();
+        let (_, f) = self.functions.get_mut(&name).expect("registered");
+        *exp = f(&args).map_err(|reason| Error::HostError(name, reason))?;
+        Ok(true)
+    }
+    /// Evaluate by [`Host::step`] for at most [`SIMPLIFY_LIMIT`] steps.
+    pub fn eval<'b>(&mut self, exp: &'b mut ExpExt) -> Result<&'b mut ExpExt, Error> {
+        for _ in 0..SIMPLIFY_LIMIT {
+            if !self.step(exp)? {
+                return Ok(exp);
+            }
+        }
+        Err(Error::SimplifyLimitExceeded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Host;
+    use crate::{
+        applied::{parse, Const, ExpExt},
+        Error,
+    };
+    use std::cell::RefCell;
+
+    #[test]
+    fn test_order() -> Result<(), Error> {
+        let log = RefCell::new(vec![]);
+        let mut host = Host::default();
+        host.register("print", 1, |args| {
+            log.borrow_mut().push(args[0].to_string());
+            Ok(args[0].clone())
+        });
+        let mut count = 0;
+        host.register("tick", 0, move |_| {
+            count += 1;
+            Ok(ExpExt::from(Const::Int(count)))
+        });
+        host.register("fail", 1, |_| Err(String::from("failed")));
+
+        // arguments from left to right, before the function is applied
+        let mut e = parse("(\\x y. print y) (print 1) (print 2)")?;
+        host.eval(&mut e)?;
+        assert_eq!(*log.borrow(), ["1", "2", "2"]);
+
+        // only the branch taken is evaluated, bodies of abstractions aren't
+        log.borrow_mut().clear();
+        let mut e = parse("if (tick == 1) (print tick) (print 0) (\\x. print x)")?;
+        host.eval(&mut e)?;
+        assert_eq!(*log.borrow(), ["2"]);
+        assert_eq!(e.to_string(), parse("2 (\\x. print x)")?.to_string());
+
+        // partial applications are values
+        let mut e = parse("(\\f. f 1) print")?;
+        host.eval(&mut e)?;
+        assert_eq!(e.as_const(), Some(&Const::Int(1)));
+
+        let mut e = parse("(\\x. x) (fail 1)")?;
+        assert!(matches!(host.eval(&mut e), Err(Error::HostError(name, _)) if name == "fail"));
+        drop(host);
+        assert_eq!(*log.borrow(), ["2", "1"]);
+        Ok(())
+    }
+}
@@ -22,17 +22,35 @@
 mod error;
 mod eval;
 mod exp;
+mod quote;
 
+pub mod applied;
+pub mod blc;
 #[doc(hidden)]
 pub mod builder;
+pub mod cps;
+pub mod data;
+pub mod decode;
+pub mod env;
+pub mod esolang;
+pub mod host;
+pub mod loader;
+pub mod module;
+pub mod numeral;
 pub mod parser;
+pub mod prelude;
+pub mod pretty;
+pub mod rules;
+pub mod ski;
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
-pub use error::Error;
-pub use eval::SIMPLIFY_LIMIT;
+pub use error::{Error, ParseError};
+pub use eval::{EvalOptions, EvalStats, Rewrite, SIMPLIFY_LIMIT};
+pub use exp::Branch;
 pub use exp::Exp;
 pub use exp::Ident;
+pub use exp::Path;
 
 #[cfg(test)]
 mod tests {
@@ -133,6 +151,7 @@ mod tests {
         println!("test = {:#}", test);
 
         assert_eq!(test.to_string(), nats[7].to_string());
+        assert_eq!(test.as_church_nat(), Some(7));
         Ok(())
     }
 }
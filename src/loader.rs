@@ -0,0 +1,456 @@
+//! Loading definition files with their imports.
+use crate::{
+    data::DataType,
+    module::{Definition, Module},
+    parser::{self, Fixity, ParseOptions},
+    Error, Exp, Ident,
+};
+use std::{
+    collections::HashMap,
+    path::{Component, Path, PathBuf},
+};
+
+/// Source of files, which identifies and reads imported files.
+pub trait Resolver {
+    /// Identify the file `path` imported by the file `base`.
+    ///
+    /// By default, `/`-separated paths are joined relative to the directory
+    /// of `base`, e. g. `lib/list.lambda` imports `../bool.lambda` as
+    /// `bool.lambda`.
+    fn resolve(&self, base: &str, path: &str) -> String {
+        let mut parts: Vec<&str> = base.split('/').collect();
+        parts.pop();
+        for part in path.split('/') {
+            match part {
+                "." | "" => {}
+                ".." if parts.last().is_some_and(|p| *p != "..") => {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        parts.join("/")
+    }
+    /// Read the content of the file identified by `name`.
+    fn read(&self, name: &str) -> Result<String, String>;
+}
+
+/// Resolver reading files from the file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileResolver;
+
+impl Resolver for FileResolver {
+    /// Join `path` relative to the directory of `base`, removing `.` and
+    /// `..` so that the same file is always identified by the same name.
+    fn resolve(&self, base: &str, path: &str) -> String {
+        let base = Path::new(base);
+        let dir = base.parent().unwrap_or(base);
+        let mut parts = PathBuf::new();
+        for part in dir.join(path).components() {
+            match part {
+                Component::CurDir => {}
+                Component::ParentDir
+                    if matches!(parts.components().next_back(), Some(Component::Normal(_))) =>
+                {
+                    parts.pop();
+                }
+                part => parts.push(part),
+            }
+        }
+        parts.to_string_lossy().into_owned()
+    }
+    fn read(&self, name: &str) -> Result<String, String> {
+        std::fs::read_to_string(name).map_err(|e| e.to_string())
+    }
+}
+
+/// Resolver of files in memory, e. g. for the browser.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryResolver {
+    files: HashMap<String, String>,
+}
+
+impl MemoryResolver {
+    /// New instance without files
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add or replace a file.
+    pub fn insert(&mut self, name: impl Into<String>, content: impl Into<String>) {
+        self.files.insert(name.into(), content.into());
+    }
+}
+
+impl Resolver for MemoryResolver {
+    fn read(&self, name: &str) -> Result<String, String> {
+        self.files
+            .get(name)
+            .cloned()
+            .ok_or_else(|| String::from("file not found"))
+    }
+}
+
+/// Rename free variables by `rename`.
+fn rename_free(exp: &mut Exp<String>, rename: impl Fn(&str) -> Option<String> + Clone) {
+    exp.for_each_var(|v, _| {
+        if let Exp::Var(Ident(name, 0)) = v {
+            if let Some(new_name) = rename(name) {
+                *name = new_name;
+            }
+        }
+    });
+}
+
+/// Loader of definition files, resolving `import "path"` by a [`Resolver`].
+///
+/// The definitions of an imported file are qualified by the alias of the
+/// import, e. g. `Bool.and`. Its own definitions can be referred to without
+/// the alias too, unless defined in the importing file. Definitions
+/// imported by the imported file are only accessible qualified twice, e. g.
+/// `List.Bool.and`.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::{env::Environment, lambda, loader::{Loader, MemoryResolver}};
+/// let mut files = MemoryResolver::new();
+/// files.insert("lib/bool.lambda", r"True = \x y. x
+/// False = \x y. y
+/// and = \p q. p q p");
+/// files.insert("main.lambda", r#"import "lib/bool.lambda"
+/// both = \x. Bool.and x True"#);
+///
+/// let module = Loader::new(files).load("main.lambda").unwrap();
+/// let names: Vec<_> = module.visible().map(|d| d.name.as_str()).collect();
+/// assert_eq!(names, ["Bool.True", "Bool.False", "Bool.and", "both"]);
+///
+/// let env = Environment::from_module(&module).unwrap();
+/// let mut e = lambda!(both "Bool.False");
+/// env.simplify(&mut e).unwrap();
+/// assert_eq!(e, lambda!(x. y. y));
+/// ```
+pub struct Loader<R: Resolver> {
+    resolver: R,
+    options: ParseOptions,
+    cache: HashMap<String, Module>,
+}
+
+impl<R: Resolver> Loader<R> {
+    /// New instance reading files by `resolver`
+    pub fn new(resolver: R) -> Self {
+        Self {
+            resolver,
+            options: ParseOptions::default(),
+            cache: HashMap::new(),
+        }
+    }
+    /// Set the options parsing every file.
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
+    /// The resolver of files.
+    pub fn resolver_mut(&mut self) -> &mut R {
+        self.cache.clear();
+        &mut self.resolver
+    }
+    /// Load the file `name` and its imports, returning a module of all
+    /// definitions, the imported ones first.
+    ///
+    /// Errors in imported files are reported as [`Error::ImportError`], and
+    /// files importing each other as [`Error::CyclicImport`].
+    pub fn load(&mut self, name: &str) -> Result<Module, Error> {
+        let content = self
+            .resolver
+            .read(name)
+            .map_err(|e| Error::ImportError(name.to_string(), Box::new(Error::ReadError(e))))?;
+        self.load_content(name, &content)
+    }
+    /// Load `content` as the file `name`, e. g. unsaved content of an editor.
+    pub fn load_content(&mut self, name: &str, content: &str) -> Result<Module, Error> {
+        self.link(name, content, &mut vec![])
+    }
+    fn import(&mut self, name: &str, stack: &mut Vec<String>) -> Result<Module, Error> {
+        if let Some(pos) = stack.iter().position(|n| n == name) {
+            let mut cycle = stack[pos..].to_vec();
+            cycle.push(name.to_string());
+            return Err(Error::CyclicImport(cycle));
+        }
+        if let Some(module) = self.cache.get(name) {
+            return Ok(module.clone());
+        }
+        let content = self
+            .resolver
+            .read(name)
+            .map_err(|e| Error::ImportError(name.to_string(), Box::new(Error::ReadError(e))))?;
+        let module = self.link(name, &content, stack).map_err(|e| match e {
+            Error::ParseError(_) | Error::UndefinedName(..) | Error::CyclicDefinition(_) => {
+                Error::ImportError(name.to_string(), Box::new(e))
+            }
+            e => e,
+        })?;
+        self.cache.insert(name.to_string(), module.clone());
+        Ok(module)
+    }
+    fn link(
+        &mut self,
+        name: &str,
+        content: &str,
+        stack: &mut Vec<String>,
+    ) -> Result<Module, Error> {
+        let imports = parser::parse_imports(content)?;
+        if imports.is_empty() {
+            return self.options.parse_module(content);
+        }
+
+        stack.push(name.to_string());
+        let mut imported = vec![];
+        for import in &imports {
+            let file = self.resolver.resolve(name, &import.path);
+            imported.push((import.alias.clone(), self.import(&file, stack)?));
+        }
+        stack.pop();
+
+        // qualify imported definitions and their references to each other
+        let mut definitions = vec![];
+        let mut fixities: HashMap<String, Fixity> = HashMap::new();
+        let mut datatypes: Vec<DataType> = vec![];
+        // data types of the imported files themselves, whose constructors
+        // can be matched unqualified
+        let mut exposed_datatypes: Vec<DataType> = vec![];
+        let mut exposed: HashMap<String, String> = HashMap::new();
+        for (alias, m) in &imported {
+            let names: Vec<&String> = m.definitions.iter().map(|d| &d.name).collect();
+            let qualify = |n: &str| {
+                names
+                    .iter()
+                    .any(|name| *name == n)
+                    .then(|| format!("{}.{}", alias, n))
+            };
+            for def in m.visible() {
+                let mut exp = def.exp.clone();
+                rename_free(&mut exp, qualify);
+                let qualified = qualify(&def.name).unwrap();
+                if !def.name.contains('.') {
+                    exposed.insert(def.name.clone(), qualified.clone());
+                }
+                definitions.push(Definition {
+                    name: qualified,
+                    exp,
+                    ..def.clone()
+                });
+            }
+            for (op, fixity) in &m.fixities {
+                let name = qualify(&fixity.name).unwrap_or_else(|| fixity.name.clone());
+                fixities.insert(
+                    op.clone(),
+                    Fixity {
+                        name,
+                        ..fixity.clone()
+                    },
+                );
+            }
+            for datatype in &m.datatypes {
+                let mut datatype = datatype.clone();
+                datatype.eliminator = qualify(&datatype.eliminator).unwrap();
+                if !datatype.name.contains('.') {
+                    exposed_datatypes.push(datatype.clone());
+                }
+                datatype.name = format!("{}.{}", alias, datatype.name);
+                for c in datatype.constructors.iter_mut() {
+                    c.name = qualify(&c.name).unwrap();
+                }
+                datatypes.push(datatype);
+            }
+        }
+
+        // parse with the imported operators and data types
+        let mut options = self.options.clone();
+        options.fixities.extend(fixities.clone());
+        options.datatypes.extend(datatypes.clone());
+        options.datatypes.extend(exposed_datatypes);
+        let mut module = options.parse_module(content)?;
+        fixities.extend(module.fixities.clone());
+        datatypes.append(&mut module.datatypes);
+
+        let local: Vec<String> = module.definitions.iter().map(|d| d.name.clone()).collect();
+        let unqualify = |n: &str| {
+            if local.iter().any(|name| name == n) {
+                None
+            } else {
+                exposed.get(n).cloned()
+            }
+        };
+        for mut def in module.definitions {
+            rename_free(&mut def.exp, unqualify);
+            definitions.push(def);
+        }
+        module.definitions = definitions;
+        module.fixities = fixities;
+        module.datatypes = datatypes;
+        Ok(module)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileResolver, Loader, MemoryResolver, Resolver};
+    use crate::{env::Environment, lambda, Error};
+
+    fn files() -> MemoryResolver {
+        let mut files = MemoryResolver::new();
+        files.insert(
+            "lib/bool.lambda",
+            r"
+            True = \x y. x
+            False = \x y. y
+            not = \p. p False True
+            infixr 3 && = and
+            and = \p q. p q p
+            ",
+        );
+        files.insert(
+            "lib/pair.lambda",
+            r#"
+            import "bool.lambda"
+            pair = \x y f. f x y
+            fst = \p. p True
+            snd = \p. p False
+            "#,
+        );
+        files.insert(
+            "main.lambda",
+            r#"
+            import "lib/pair.lambda" as P
+            import "lib/bool.lambda"
+            // `True` is redefined locally
+            True = \a b. a
+            swap = \p. pair (snd p) (fst p)
+            test = fst (swap (P.pair (not True) (P.Bool.True && True)))
+            "#,
+        );
+        files
+    }
+
+    #[test]
+    fn test_loader() -> Result<(), Error> {
+        let mut loader = Loader::new(files());
+        let module = loader.load("main.lambda")?;
+        let names: Vec<_> = module.visible().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "P.Bool.True",
+                "P.Bool.False",
+                "P.Bool.not",
+                "P.Bool.and",
+                "P.pair",
+                "P.fst",
+                "P.snd",
+                "Bool.True",
+                "Bool.False",
+                "Bool.not",
+                "Bool.and",
+                "True",
+                "swap",
+                "test"
+            ]
+        );
+        assert_eq!(
+            module.get("P.fst").unwrap().exp,
+            lambda!(p. p "P.Bool.True")
+        );
+        assert_eq!(
+            module.get("test").unwrap().exp,
+            lambda!("P.fst" (swap ("P.pair" ("Bool.not" True) ("Bool.and" "P.Bool.True" True))))
+        );
+
+        let env = Environment::from_module(&module)?;
+        let mut e = lambda!(test);
+        env.simplify(&mut e)?;
+        assert_eq!(e, lambda!(a.b.a));
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_datatypes() -> Result<(), Error> {
+        let mut files = MemoryResolver::new();
+        files.insert("option.lambda", "data Option = None | Some value");
+        files.insert(
+            "main.lambda",
+            r#"
+            import "option.lambda"
+            get = \o d. match o { None => d, Option.Some x => x }
+            test = \a b. get (Some a) b
+            "#,
+        );
+        let module = Loader::new(files).load("main.lambda")?;
+        assert_eq!(module.datatypes[0].eliminator, "Option.caseOption");
+        assert_eq!(
+            module.get("get").unwrap().exp,
+            lambda!(o. d. "Option.caseOption" o d (x. x))
+        );
+
+        let env = Environment::from_module(&module)?;
+        let mut e = lambda!(test);
+        env.simplify(&mut e)?;
+        assert_eq!(e, lambda!(a.b.a));
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let mut files = files();
+        files.insert("a.lambda", "import \"b.lambda\"\nA = \\x. x");
+        files.insert("b.lambda", "import \"./lib/../a.lambda\"\nB = \\x. x");
+        files.insert("c.lambda", "import \"d.lambda\"");
+        files.insert(
+            "e.lambda",
+            "import \"lib/bool.lambda\"\nimport \"f.lambda\"",
+        );
+        files.insert("f.lambda", "F = (");
+        assert_eq!(files.resolve("lib/x.lambda", "../a.lambda"), "a.lambda");
+
+        let mut loader = Loader::new(files);
+        let Err(Error::CyclicImport(cycle)) = loader.load("a.lambda") else {
+            panic!("expect cyclic import")
+        };
+        assert_eq!(cycle, ["a.lambda", "b.lambda", "a.lambda"]);
+        let Err(Error::ImportError(name, e)) = loader.load("c.lambda") else {
+            panic!("expect import error")
+        };
+        assert_eq!(name, "d.lambda");
+        assert!(matches!(*e, Error::ReadError(_)));
+        let Err(Error::ImportError(name, e)) = loader.load("e.lambda") else {
+            panic!("expect import error")
+        };
+        assert_eq!(name, "f.lambda");
+        // located in the imported file
+        let Error::ParseError(errors) = *e else {
+            panic!("expect parse error")
+        };
+        assert_eq!((errors[0].line, errors[0].column), (1, 6));
+    }
+
+    #[test]
+    fn test_file_resolver() -> std::io::Result<()> {
+        let dir = std::env::temp_dir().join(format!("lamcalc-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("lib"))?;
+        std::fs::write(
+            dir.join("lib/a.lambda"),
+            "import \"../lib/b.lambda\"\nA = \\x. x",
+        )?;
+        std::fs::write(dir.join("lib/b.lambda"), "import \"a.lambda\"\nB = \\x. x")?;
+
+        let a = dir.join("lib/a.lambda").to_string_lossy().into_owned();
+        let b = dir.join("lib/b.lambda").to_string_lossy().into_owned();
+        assert_eq!(FileResolver.resolve(&a, "./../lib/b.lambda"), b);
+        let result = Loader::new(FileResolver).load(&a);
+        std::fs::remove_dir_all(&dir)?;
+        let Err(Error::CyclicImport(cycle)) = result else {
+            panic!("expect cyclic import")
+        };
+        assert_eq!(cycle, [a.clone(), b, a]);
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+//! Definition files, keeping the order, docs and metadata of definitions.
+use crate::{
+    data::DataType,
+    parser::{Fixity, SpanMap, Token},
+    Exp,
+};
+use std::{collections::HashMap, ops::Range};
+
+/// A definition `name = exp` of a file.
+#[derive(Debug, Clone)]
+pub struct Definition {
+    /// Name being defined
+    pub name: String,
+    /// Definition body
+    pub exp: Exp<String>,
+    /// Comments documenting the definition, without `//` or `/* */`
+    pub doc: Option<String>,
+    /// Byte range of the whole definition in the file
+    pub span: Range<usize>,
+    /// Byte ranges of subexpressions of `exp`, see [`SpanMap`]
+    pub spans: SpanMap,
+}
+
+/// An import of a file like `import "bool.lambda" as Bool`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// Path of the imported file, relative to the importing file
+    pub path: String,
+    /// Qualifier of the imported names, e. g. `Bool` for `Bool.and`
+    pub alias: String,
+    /// Byte range of the import in the file
+    pub span: Range<usize>,
+}
+
+impl Import {
+    /// Alias of a file imported without `as`: its file stem with the first
+    /// letter capitalized, e. g. `Bool` for `lib/bool.lambda`.
+    pub fn default_alias(path: &str) -> String {
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let stem = file.split('.').next().unwrap_or(file);
+        let mut chars = stem.chars();
+        match chars.next() {
+            Some(c) => c.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+}
+
+/// Something suspicious in a file which parses nevertheless.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+pub enum Warning {
+    /// A name is defined again, shadowing the previous definition.
+    Redefined {
+        /// The name defined twice
+        name: String,
+        /// Byte range of the later definition
+        span: Range<usize>,
+        /// Line of the later definition, starting from 1
+        line: usize,
+        /// Line of the shadowed definition, starting from 1
+        previous_line: usize,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::Redefined {
+                name,
+                line,
+                previous_line,
+                ..
+            } => write!(
+                f,
+                "{}: `{}` is redefined, shadowing the definition at line {}",
+                line, name, previous_line
+            ),
+        }
+    }
+}
+
+/// A parsed definition file, see [`parse_module`](crate::parser::parse_module).
+#[derive(Debug, Clone, Default)]
+pub struct Module {
+    /// Imports in the order of the file
+    pub imports: Vec<Import>,
+    /// All definitions in the order of the file, including shadowed ones
+    pub definitions: Vec<Definition>,
+    /// Fixities declared in the file, keyed by operators
+    pub fixities: HashMap<String, Fixity>,
+    /// Data types declared in the file, whose constructors and eliminators
+    /// are among the definitions
+    pub datatypes: Vec<DataType>,
+    /// Warnings of the file
+    pub warnings: Vec<Warning>,
+    /// Tokens of the whole file, which reproduce it byte for byte
+    pub tokens: Vec<Token>,
+}
+
+impl Module {
+    /// The definition of `name` in effect, i. e. the last one.
+    pub fn get(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().rev().find(|def| def.name == name)
+    }
+    /// Definitions in the order of the file, skipping shadowed ones.
+    pub fn visible(&self) -> impl Iterator<Item = &Definition> {
+        self.definitions.iter().enumerate().filter_map(|(i, def)| {
+            let shadowed = self.definitions[i + 1..].iter().any(|d| d.name == def.name);
+            (!shadowed).then_some(def)
+        })
+    }
+    /// Map from names to the definitions in effect.
+    pub fn to_map(&self) -> HashMap<String, Exp<String>> {
+        self.visible()
+            .map(|def| (def.name.clone(), def.exp.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lambda, parser::parse_module, Error};
+
+    use super::Warning;
+
+    #[test]
+    fn test_module() -> Result<(), Error> {
+        let lambda = r"// identity
+I = \x. x
+
+// true,
+// selecting the first one
+T = \x. \y. x
+K = T // same as `T`
+/* the S combinator */ S = \x y z. x z (y z)
+I = \y. y
+";
+        let module = parse_module(lambda)?;
+        let names: Vec<_> = module.definitions.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["I", "T", "K", "S", "I"]);
+        let names: Vec<_> = module.visible().map(|d| d.name.as_str()).collect();
+        assert_eq!(names, ["T", "K", "S", "I"]);
+
+        let docs: Vec<_> = module
+            .definitions
+            .iter()
+            .map(|d| d.doc.as_deref())
+            .collect();
+        assert_eq!(
+            docs,
+            [
+                Some("identity"),
+                Some("true,\nselecting the first one"),
+                Some("same as `T`"),
+                Some("the S combinator"),
+                None
+            ]
+        );
+
+        let t = module.get("T").unwrap();
+        assert_eq!(&lambda[t.span.clone()], r"T = \x. \y. x");
+        assert_eq!(module.get("I").unwrap().exp, lambda!(y.y));
+        assert_eq!(module.to_map()["S"], lambda!(x. y. z. x z (y z)));
+
+        assert_eq!(
+            module.warnings,
+            [Warning::Redefined {
+                name: String::from("I"),
+                span: lambda.rfind("I = ").unwrap()..lambda.len() - 1,
+                line: 9,
+                previous_line: 2,
+            }]
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+//! Natural numbers encoded as lambda expressions.
+//!
+//! | encoding  | 0                 | n + 1                 |
+//! |-----------|-------------------|-----------------------|
+//! | Church    | `λf. λx. x`       | `λf. λx. f (n f x)`   |
+//! | Scott     | `λz. λs. z`       | `λz. λs. s n`         |
+//!
+//! The binary encoding represents a number by its bits, least
+//! significant first: `0` is `λe. λo. λi. e`, `2n` is `λe. λo. λi. o n`
+//! and `2n + 1` is `λe. λo. λi. i n`, where `n > 0` for `2n`.
+use crate::{Exp, Ident};
+
+/// Encoding of natural numbers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// Church numerals, `n` applications of `f` to `x`
+    #[default]
+    Church,
+    /// Scott numerals, supporting constant-time predecessor
+    Scott,
+    /// Binary numerals, of size logarithmic in the number
+    Binary,
+}
+
+/// Largest number literal in the unary encodings, Church and Scott, whose
+/// numerals take a node per unit, so that recursion over them, e. g. when
+/// printing, fits the default stack.
+pub const MAX_UNARY: u64 = 256;
+
+impl Encoding {
+    /// Largest number literal encoded, [`MAX_UNARY`] for the unary
+    /// encodings and unbounded for binary numerals.
+    pub fn max_literal(self) -> u64 {
+        match self {
+            Encoding::Church | Encoding::Scott => MAX_UNARY,
+            Encoding::Binary => u64::MAX,
+        }
+    }
+}
+
+/// Encode `n` as a closed lambda expression.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::{lambda, numeral::{encode, Encoding}};
+/// assert_eq!(encode(2, Encoding::Church), lambda!(f. x. f (f x)));
+/// assert_eq!(encode(1, Encoding::Scott), lambda!(z. s. s (z. s. z)));
+/// ```
+pub fn encode(n: u64, encoding: Encoding) -> Exp<String> {
+    match encoding {
+        Encoding::Church => Exp::from_church_num(n, String::from("f"), String::from("x")),
+        Encoding::Scott => {
+            let mut cur = Exp::abs("z", Exp::abs("s", Exp::var("z", 2)));
+            for _ in 0..n {
+                cur = Exp::abs("z", Exp::abs("s", Exp::app(Exp::var("s", 1), cur)));
+            }
+            cur
+        }
+        Encoding::Binary => {
+            let body = match n {
+                0 => Exp::var("e", 3),
+                n if n % 2 == 0 => Exp::app(Exp::var("o", 2), encode(n / 2, encoding)),
+                n => Exp::app(Exp::var("i", 1), encode(n / 2, encoding)),
+            };
+            Exp::abs("e", Exp::abs("o", Exp::abs("i", body)))
+        }
+    }
+}
+
+/// Decode a numeral of `encoding`, return `None` if `exp` isn't one.
+///
+/// Non-canonical binary numerals, i. e. `2n` with `n = 0`, are accepted.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::{lambda, numeral::{decode, Encoding}};
+/// assert_eq!(decode(&lambda!(f. x. f (f x)), Encoding::Church), Some(2));
+/// assert_eq!(decode(&lambda!(f. x. f (f x)), Encoding::Scott), None);
+/// ```
+pub fn decode<T: Clone + Eq>(exp: &Exp<T>, encoding: Encoding) -> Option<u64> {
+    match encoding {
+        Encoding::Church => exp.try_into_church_num().map(|(n, _, _)| n),
+        Encoding::Scott => {
+            let (_, body) = exp.into_abs()?;
+            let (_, body) = body.into_abs()?;
+            match body {
+                Exp::Var(Ident(_, 2)) => Some(0),
+                Exp::App(s, pred) if s.into_ident()?.1 == 1 => {
+                    decode(pred, encoding).map(|n| n + 1)
+                }
+                _ => None,
+            }
+        }
+        Encoding::Binary => {
+            let (_, body) = exp.into_abs()?;
+            let (_, body) = body.into_abs()?;
+            let (_, body) = body.into_abs()?;
+            match body {
+                Exp::Var(Ident(_, 3)) => Some(0),
+                Exp::App(bit, half) => {
+                    let half = decode(half, encoding)?.checked_mul(2)?;
+                    match bit.into_ident()?.1 {
+                        2 => Some(half),
+                        1 => half.checked_add(1),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, encode, Encoding};
+    use crate::{lambda, Error};
+
+    #[test]
+    fn test_round_trip() {
+        for encoding in [Encoding::Church, Encoding::Scott, Encoding::Binary] {
+            for n in [0, 1, 2, 5, 6, 42, 1000] {
+                assert_eq!(decode(&encode(n, encoding), encoding), Some(n));
+            }
+        }
+        assert_eq!(
+            encode(6, Encoding::Binary).purify(),
+            lambda!(e.o.i.o(e.o.i.i(e.o.i.i(e.o.i.e)))).purify()
+        );
+        assert_eq!(decode(&lambda!(z.s.s(z.s.s)), Encoding::Scott), None);
+    }
+    #[test]
+    fn test_arithmetic() -> Result<(), Error> {
+        let suc = lambda!(n. f. x. f (n f x));
+        let mut e = lambda!({suc} {encode(41, Encoding::Church)});
+        e.simplify(false)?;
+        assert_eq!(decode(&e, Encoding::Church), Some(42));
+
+        let pred = lambda!(n. n {encode(0, Encoding::Scott)} (m. m));
+        let mut e = lambda!({pred} {encode(42, Encoding::Scott)});
+        e.simplify(false)?;
+        assert_eq!(decode(&e, Encoding::Scott), Some(41));
+        Ok(())
+    }
+}
@@ -11,21 +11,48 @@
 //! ```
 //!
 
-use crate::{builder, Error, Exp};
-use pest::{iterators::Pair, Parser};
+use crate::{
+    builder,
+    data::{self, Constructor, DataType, Field},
+    error::line_of,
+    module::{Definition, Import, Module, Warning},
+    numeral, Branch, Error, Exp, Ident, ParseError, Path,
+};
+use pest::{
+    iterators::{Pair, Pairs},
+    Parser,
+};
 use pest_derive::Parser;
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
 /// lambda expression parser using [pest](https://pest.rs/)
 #[derive(Parser)]
 #[grammar = "./grammar.pest"]
 pub struct LambdaParser;
 
-/// Token of lambda expression
-#[derive(Debug)]
+/// Byte ranges of subexpressions, keyed by their paths from the root expression.
+///
+/// Nodes introduced by desugaring, such as the abstraction of `let`, the
+/// inside of numerals and partial applications of infix operators, have no
+/// span.
+pub type SpanMap = HashMap<Path, Range<usize>>;
+
+/// Token of lambda expression with its location in the source text
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "wasm", derive(serde::Serialize))]
+pub struct Token {
+    /// Kind and content of the token
+    #[cfg_attr(feature = "wasm", serde(flatten))]
+    pub kind: TokenKind,
+    /// Byte range in the source text
+    pub span: Range<usize>,
+}
+
+/// Kind of token
+#[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "wasm", derive(serde::Serialize))]
 #[cfg_attr(feature = "wasm", serde(tag = "kind", content = "data"))]
-pub enum Token {
+pub enum TokenKind {
     /// Dot symbol `.`
     DotSym,
     /// Left parenthesis `(`
@@ -34,25 +61,326 @@ pub enum Token {
     RPar,
     /// Equal symbol `=`
     Eq,
-    /// Line feed `\n`
-    LineFeed,
+    /// Line feed `\n` or `\r\n`
+    LineFeed(String),
     /// Lambda symbol `\`
     LamSym(String),
     /// String consists of blank characters
     Blank(String),
     /// Variable identifier
     Ident(String),
-    /// Comment
+    /// Comment, `// ...` or `/* ... */`
     Comment(String),
+    /// Keyword `let` or `in`
+    Keyword(String),
+    /// Number literal
+    Number(String),
+    /// Infix operator
+    Operator(String),
+    /// Double-quoted string
+    Str(String),
+    /// Punctuation of data declarations and `match`, i. e. `|`, `:`, `{`,
+    /// `}`, `,` and `=>`
+    Symbol(String),
+}
+
+fn rule_name(rule: Rule) -> String {
+    match rule {
+        Rule::ident | Rule::qualified => "identifier",
+        Rule::string => "string",
+        Rule::import_kw => "`import`",
+        Rule::as_kw => "`as`",
+        Rule::num => "number",
+        Rule::op => "operator",
+        Rule::assoc => "`infixl`, `infixr` or `infix`",
+        Rule::precedence => "precedence",
+        Rule::exp
+        | Rule::tail_exp
+        | Rule::bounded_exp
+        | Rule::app
+        | Rule::abs
+        | Rule::let_exp
+        | Rule::match_exp => "expression",
+        Rule::let_kw => "`let`",
+        Rule::match_kw => "`match`",
+        Rule::data_kw => "`data`",
+        Rule::data_encoding => "`church` or `scott`",
+        Rule::lbrace => "`{`",
+        Rule::rbrace => "`}`",
+        Rule::comma => "`,`",
+        Rule::arrow => "`=>`",
+        Rule::bar => "`|`",
+        Rule::colon => "`:`",
+        Rule::pattern => "pattern",
+        Rule::arm => "arm",
+        Rule::field => "field",
+        Rule::constructor => "constructor",
+        Rule::in_kw => "`in`",
+        Rule::dot => "`.`",
+        Rule::lamsym => "`\\`",
+        Rule::eq => "`=`",
+        Rule::blank | Rule::inline_blank | Rule::spaces | Rule::indent => "blank",
+        Rule::indent_more | Rule::indent_close => "indentation",
+        Rule::block_com => "comment",
+        Rule::newline => "line feed",
+        Rule::com => "comment",
+        Rule::def => "definition",
+        Rule::db_index => "index",
+        Rule::db_exp | Rule::db_abs | Rule::db_app => "nameless expression",
+        Rule::EOI => "end of input",
+        rule => return format!("{:?}", rule),
+    }
+    .to_string()
+}
+
+pub(crate) fn join_names(names: &[String]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.clone(),
+        [init @ .., last] => format!("{} or {}", init.join(", "), last),
+    }
+}
+
+/// Guess a fix for the failure at `pos`.
+fn help_message(input: &str, pos: usize, expected: &[String]) -> Option<String> {
+    if input[pos..].starts_with('λ') {
+        return Some(String::from("use `\\` for lambda"));
+    }
+    let line_start = input[..pos].rfind('\n').map_or(0, |p| p + 1);
+    let line_end = input[pos..].find('\n').map_or(input.len(), |p| pos + p);
+    let line = &input[line_start..line_end];
+    if line.matches('(').count() != line.matches(')').count() {
+        return Some(String::from("check that parentheses are balanced"));
+    }
+    if expected.iter().any(|name| name == "`.`") {
+        return Some(String::from("abstraction is written as `\\x. body`"));
+    }
+    if expected.iter().any(|name| name == "`=`") {
+        return Some(String::from("definition is written as `name = expression`"));
+    }
+    None
+}
+
+/// Convert pest's error on `input[offset..]` to a diagnostic of `input`.
+fn pest_error(input: &str, offset: usize, e: pest::error::Error<Rule>) -> ParseError {
+    use pest::error::{ErrorVariant, InputLocation};
+
+    let span = match e.location {
+        InputLocation::Pos(pos) => {
+            let pos = offset + pos;
+            pos..input[pos..]
+                .chars()
+                .next()
+                .map_or(pos, |c| pos + c.len_utf8())
+        }
+        InputLocation::Span((start, end)) => offset + start..offset + end,
+    };
+    let found = match input[span.start..].chars().next() {
+        Some('\r' | '\n') => String::from("end of line"),
+        Some(c) => format!("`{}`", c),
+        None => String::from("end of input"),
+    };
+    let (message, expected) = match e.variant {
+        ErrorVariant::ParsingError {
+            positives,
+            negatives,
+        } => {
+            let mut expected: Vec<String> = vec![];
+            for name in positives.into_iter().map(rule_name) {
+                if !expected.contains(&name) {
+                    expected.push(name);
+                }
+            }
+            let unexpected: Vec<String> = negatives.into_iter().map(rule_name).collect();
+            let message = if !expected.is_empty() {
+                format!("expected {}, found {}", join_names(&expected), found)
+            } else if !unexpected.is_empty() {
+                format!("unexpected {}", join_names(&unexpected))
+            } else {
+                format!("unexpected {}", found)
+            };
+            (message, expected)
+        }
+        ErrorVariant::CustomError { message } => (message, vec![]),
+    };
+    let help = help_message(input, span.start, &expected);
+    ParseError {
+        expected,
+        help,
+        ..ParseError::new(input, span, message)
+    }
+}
+
+/// Split a file into statements, each of which is a line with its
+/// continuation lines, and return their offsets.
+///
+/// A line continues the statement if it's indented deeper, if it's indented
+/// or closes parentheses (or braces) within unclosed ones, or if it's in a
+/// block comment.
+fn statements(lambda: &str) -> Vec<(usize, &str)> {
+    fn indent(line: &str) -> &str {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        &line[..line.len() - trimmed.len()]
+    }
+    let mut res: Vec<(usize, &str)> = vec![];
+    let mut offset = 0;
+    for line in lambda.split('\n') {
+        let continued = res.last().is_some_and(|&(_, stmt)| {
+            let first = indent(stmt);
+            let cur = indent(line);
+            let in_com = stmt.matches("/*").count() > stmt.matches("*/").count();
+            let in_paren = stmt.matches(['(', '{']).count() > stmt.matches([')', '}']).count();
+            in_com
+                || in_paren && (!cur.is_empty() || line.starts_with([')', '}']))
+                || cur.len() > first.len() && cur.starts_with(first)
+        });
+        match res.last_mut() {
+            Some((start, stmt)) if continued => *stmt = &lambda[*start..offset + line.len()],
+            _ => res.push((offset, line)),
+        }
+        offset += line.len() + 1;
+    }
+    res
+}
+
+/// Parse each statement on its own to report every bad statement of a file.
+fn line_errors(lambda: &str, e: pest::error::Error<Rule>) -> Vec<ParseError> {
+    let mut errors = vec![];
+    for (offset, stmt) in statements(lambda) {
+        let text = stmt.strip_suffix('\r').unwrap_or(stmt);
+        if let Err(e) = LambdaParser::parse(Rule::single_line, text) {
+            errors.push(pest_error(lambda, offset, e));
+        }
+    }
+    if errors.is_empty() {
+        errors.push(pest_error(lambda, 0, e));
+    }
+    errors
+}
+
+/// Name of an identifier, without quoting backticks.
+fn ident_name(tt: &Pair<Rule>) -> String {
+    let s = tt.as_str();
+    s.strip_prefix('`')
+        .and_then(|s| s.strip_suffix('`'))
+        .unwrap_or(s)
+        .to_string()
+}
+
+fn token(kind: TokenKind, span: pest::Span) -> Token {
+    Token {
+        kind,
+        span: span.start()..span.end(),
+    }
+}
+
+fn text_token(kind: fn(String) -> TokenKind, tt: &Pair<Rule>) -> Token {
+    token(kind(tt.as_str().to_string()), tt.as_span())
+}
+
+/// Text of a comment without `//` or `/* */`.
+fn comment_text(com: &str) -> String {
+    match com.strip_prefix("//") {
+        Some(text) => text
+            .strip_prefix(' ')
+            .unwrap_or(text)
+            .trim_end()
+            .to_string(),
+        None => com
+            .trim_start_matches("/*")
+            .trim_end_matches("*/")
+            .trim()
+            .to_string(),
+    }
+}
+
+/// Tokens of a `blank` or `inline_blank`, which may contain comments and line
+/// feeds.
+fn blank_tokens(tt: &Pair<Rule>) -> Vec<Token> {
+    tt.clone()
+        .into_inner()
+        .filter(|cur| !cur.as_str().is_empty())
+        .map(|cur| match cur.as_rule() {
+            Rule::block_com | Rule::com => text_token(TokenKind::Comment, &cur),
+            Rule::newline => text_token(TokenKind::LineFeed, &cur),
+            _ => text_token(TokenKind::Blank, &cur),
+        })
+        .collect()
+}
+
+/// Prepend `branches` to every path of `spans`.
+fn prefix_spans(spans: SpanMap, branches: &[Branch]) -> SpanMap {
+    spans
+        .into_iter()
+        .map(|(path, span)| {
+            let mut full = branches.to_vec();
+            full.extend(path.0);
+            (Path(full), span)
+        })
+        .collect()
+}
+
+fn literal_kind(rule: Rule) -> fn(String) -> TokenKind {
+    match rule {
+        Rule::string => TokenKind::Str,
+        _ => TokenKind::Number,
+    }
 }
 
-fn build_lambda(tt: Pair<Rule>) -> Result<(Exp<String>, Vec<Token>), Error> {
+fn build_lambda(
+    mut tt: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<(Exp<String>, Vec<Token>, SpanMap), Error> {
+    // unwrap rules of a single expression in place, so that nested
+    // parentheses take fewer stack frames
+    while let Rule::exp | Rule::operand | Rule::app | Rule::tail_exp = tt.as_rule() {
+        let mut inner = tt.clone().into_inner();
+        match (inner.next(), inner.next()) {
+            (Some(only), None) => tt = only,
+            _ => break,
+        }
+    }
+    let span = tt.as_span();
     match tt.as_rule() {
-        Rule::ident => {
-            let ident = tt.as_span().as_str().to_string();
+        Rule::ident | Rule::qualified => Ok((
+            builder::unbounded_var(ident_name(&tt)),
+            vec![text_token(TokenKind::Ident, &tt)],
+            HashMap::from([(Path::default(), span.start()..span.end())]),
+        )),
+        Rule::num | Rule::string if options.native_literals => Ok((
+            builder::unbounded_var(span.as_str().to_string()),
+            vec![text_token(literal_kind(tt.as_rule()), &tt)],
+            HashMap::from([(Path::default(), span.start()..span.end())]),
+        )),
+        Rule::string => {
+            let message = String::from("string literals are only allowed as native literals");
+            let range = span.start()..span.end();
+            Err(Error::ParseError(vec![ParseError {
+                help: Some(String::from("set `native_literals` in the parse options")),
+                ..ParseError::new(span.get_input(), range, message)
+            }]))
+        }
+        Rule::num => {
+            let error = |message| {
+                let range = span.start()..span.end();
+                Error::ParseError(vec![ParseError::new(span.get_input(), range, message)])
+            };
+            let n: u64 = span
+                .as_str()
+                .parse()
+                .map_err(|e| error(format!("invalid number {}: {}", span.as_str(), e)))?;
+            let max = options.numeral.max_literal();
+            if n > max {
+                let message = format!(
+                    "number {} exceeds {} of {:?} numerals",
+                    n, max, options.numeral
+                );
+                return Err(error(message));
+            }
             Ok((
-                builder::unbounded_var(ident.clone()),
-                vec![Token::Ident(ident)],
+                numeral::encode(n, options.numeral),
+                vec![text_token(TokenKind::Number, &tt)],
+                HashMap::from([(Path::default(), span.start()..span.end())]),
             ))
         }
         Rule::abs => {
@@ -60,47 +388,92 @@ fn build_lambda(tt: Pair<Rule>) -> Result<(Exp<String>, Vec<Token>), Error> {
             let mut tks = vec![];
 
             let mut cur = it.next().unwrap();
-            tks.push(Token::LamSym(cur.as_span().as_str().to_string()));
-            cur = it.next().unwrap();
-
-            let ident = cur.as_span().as_str().to_string();
-            tks.push(Token::Ident(ident.clone()));
-            cur = it.next().unwrap();
+            tks.push(text_token(TokenKind::LamSym, &cur));
 
-            if let Rule::blank = cur.as_rule() {
-                tks.push(Token::Blank(cur.as_span().as_str().to_string()));
-                cur = it.next().unwrap()
-            }
-            if let Rule::dot = cur.as_rule() {
-                tks.push(Token::DotSym);
-                cur = it.next().unwrap()
-            } else {
-                panic!("invalid parsing rule: {}", cur)
+            let mut params = vec![];
+            loop {
+                cur = it.next().unwrap();
+                match cur.as_rule() {
+                    Rule::ident => {
+                        params.push((ident_name(&cur), cur.as_span().start()));
+                        tks.push(text_token(TokenKind::Ident, &cur));
+                    }
+                    Rule::blank => tks.extend(blank_tokens(&cur)),
+                    Rule::dot => {
+                        tks.push(token(TokenKind::DotSym, cur.as_span()));
+                        break;
+                    }
+                    _ => panic!("invalid parsing rule: {}", cur),
+                }
             }
+            cur = it.next().unwrap();
             if let Rule::blank = cur.as_rule() {
-                tks.push(Token::Blank(cur.as_span().as_str().to_string()));
+                tks.extend(blank_tokens(&cur));
                 cur = it.next().unwrap()
             }
             if let Rule::exp = cur.as_rule() {
-                let (body_exp, mut body_tks) = build_lambda(cur)?;
+                let (mut exp, mut body_tks, mut spans) = build_lambda(cur, options)?;
                 tks.append(&mut body_tks);
-                Ok((builder::abs(ident, body_exp), tks))
+                // `\x y. e` is `\x. \y. e`, where the inner abstraction starts at `y`
+                for (k, (ident, start)) in params.into_iter().enumerate().rev() {
+                    exp = builder::abs(ident, exp);
+                    spans = prefix_spans(spans, &[Branch::AbsBody]);
+                    let start = if k == 0 { span.start() } else { start };
+                    spans.insert(Path::default(), start..span.end());
+                }
+                Ok((exp, tks, spans))
             } else {
                 panic!("invalid parsing rule: {}", cur)
             }
         }
+        Rule::let_exp => {
+            let mut tks = vec![];
+            let mut ident = String::new();
+            let mut parts = vec![];
+            for cur in tt.into_inner() {
+                match cur.as_rule() {
+                    Rule::let_kw | Rule::in_kw => tks.push(text_token(TokenKind::Keyword, &cur)),
+                    Rule::blank => tks.extend(blank_tokens(&cur)),
+                    Rule::ident => {
+                        ident = ident_name(&cur);
+                        tks.push(text_token(TokenKind::Ident, &cur));
+                    }
+                    Rule::eq => tks.push(token(TokenKind::Eq, cur.as_span())),
+                    Rule::exp => {
+                        let (exp, mut exp_tks, spans) = build_lambda(cur, options)?;
+                        tks.append(&mut exp_tks);
+                        parts.push((exp, spans));
+                    }
+                    _ => panic!("invalid parsing rule: {}", cur),
+                }
+            }
+            let (body, body_spans) = parts.pop().unwrap();
+            let (value, value_spans) = parts.pop().unwrap();
+            // the abstraction introduced by desugaring has no span
+            let mut spans = prefix_spans(body_spans, &[Branch::AppFunc, Branch::AbsBody]);
+            spans.extend(prefix_spans(value_spans, &[Branch::AppBody]));
+            spans.insert(Path::default(), span.start()..span.end());
+            Ok((
+                builder::app(vec![builder::abs(ident, body), value]),
+                tks,
+                spans,
+            ))
+        }
         Rule::app => {
             let mut it = tt.into_inner();
             let mut cur = it.next().unwrap();
             let mut exprs = vec![];
             let mut tks = vec![];
+            let mut parts = vec![];
             loop {
                 if let Rule::tail_exp | Rule::bounded_exp = cur.as_rule() {
-                    let (cur_exp, mut cur_tks) = build_lambda(cur)?;
+                    let end = cur.as_span().end();
+                    let (cur_exp, mut cur_tks, cur_spans) = build_lambda(cur, options)?;
                     exprs.push(cur_exp);
                     tks.append(&mut cur_tks);
+                    parts.push((end, cur_spans));
                 } else if let Rule::blank = cur.as_rule() {
-                    tks.push(Token::Blank(cur.as_span().as_str().to_string()));
+                    tks.extend(blank_tokens(&cur));
                 } else {
                     panic!("invalid parsing rule: {}", cur)
                 }
@@ -111,91 +484,793 @@ fn build_lambda(tt: Pair<Rule>) -> Result<(Exp<String>, Vec<Token>), Error> {
                     }
                 };
             }
-            Ok((builder::app(exprs), tks))
+            // the k-th expression is the body of the application nested in
+            // (n - k) functions, except the first one.
+            let n = parts.len();
+            let mut spans = HashMap::new();
+            for (k, (end, part)) in parts.into_iter().enumerate() {
+                let mut branches = vec![Branch::AppFunc; n - 1 - k];
+                if k > 0 {
+                    spans.insert(Path(branches.clone()), span.start()..end);
+                    branches.push(Branch::AppBody);
+                }
+                spans.extend(prefix_spans(part, &branches));
+            }
+            Ok((builder::app(exprs), tks, spans))
         }
 
-        Rule::exp | Rule::tail_exp => build_lambda(tt.into_inner().next().unwrap()),
+        Rule::op => Ok((
+            builder::unbounded_var(options.fixity(tt.as_str()).name),
+            vec![text_token(TokenKind::Operator, &tt)],
+            HashMap::from([(Path::default(), span.start()..span.end())]),
+        )),
+
+        Rule::exp => {
+            let mut tks = vec![];
+            let mut operands = vec![];
+            let mut ops = vec![];
+            for cur in tt.into_inner() {
+                match cur.as_rule() {
+                    Rule::operand => {
+                        let range = cur.as_span().start()..cur.as_span().end();
+                        let (exp, mut exp_tks, spans) = build_lambda(cur, options)?;
+                        tks.append(&mut exp_tks);
+                        operands.push((exp, spans, range));
+                    }
+                    Rule::op => {
+                        tks.push(text_token(TokenKind::Operator, &cur));
+                        ops.push(cur);
+                    }
+                    Rule::blank => tks.extend(blank_tokens(&cur)),
+                    _ => panic!("invalid parsing rule: {}", cur),
+                }
+            }
+            let (exp, spans) = resolve_infix(operands, ops, options)?;
+            Ok((exp, tks, spans))
+        }
 
         Rule::bounded_exp => {
             let mut tks = vec![];
             let mut it = tt.into_inner();
             let mut cur = it.next().unwrap();
 
-            if let Rule::ident = cur.as_rule() {
-                return build_lambda(cur);
+            if let Rule::ident | Rule::num | Rule::string = cur.as_rule() {
+                return build_lambda(cur, options);
             }
 
-            tks.push(Token::LPar);
+            tks.push(Token {
+                kind: TokenKind::LPar,
+                span: span.start()..span.start() + 1,
+            });
             if let Rule::blank = cur.as_rule() {
-                tks.push(Token::Blank(cur.as_span().as_str().to_string()));
+                tks.extend(blank_tokens(&cur));
                 cur = it.next().unwrap();
             }
-            let (exp, mut exp_tks) = build_lambda(cur)?;
+            let (exp, mut exp_tks, spans) = build_lambda(cur, options)?;
             tks.append(&mut exp_tks);
             if let Some(nex) = it.next() {
-                tks.push(Token::Blank(nex.as_span().as_str().to_string()));
+                tks.push(text_token(TokenKind::Blank, &nex));
             }
-            tks.push(Token::RPar);
+            tks.push(Token {
+                kind: TokenKind::RPar,
+                span: span.end() - 1..span.end(),
+            });
 
-            Ok((exp, tks))
+            Ok((exp, tks, spans))
         }
 
+        Rule::match_exp => build_match(tt, options),
+
         _ => panic!("invalid parsing rule: {}", tt),
     }
 }
 
-fn build_def(tt: Pair<Rule>) -> Result<(String, Exp<String>, Vec<Token>), Error> {
+/// An arm of `match` with the byte range of its pattern.
+struct Arm {
+    constructor: String,
+    params: Vec<String>,
+    range: Range<usize>,
+    exp: Exp<String>,
+    spans: SpanMap,
+}
+
+fn build_arm(tt: Pair<Rule>, tks: &mut Vec<Token>, options: &ParseOptions) -> Result<Arm, Error> {
+    let mut arm = Arm {
+        constructor: String::new(),
+        params: vec![],
+        range: 0..0,
+        exp: builder::unbounded_var(String::new()),
+        spans: SpanMap::new(),
+    };
+    for cur in tt.into_inner() {
+        match cur.as_rule() {
+            Rule::pattern => {
+                arm.range = cur.as_span().start()..cur.as_span().end();
+                for (k, part) in cur.into_inner().enumerate() {
+                    match part.as_rule() {
+                        Rule::qualified | Rule::ident if k == 0 => {
+                            arm.constructor = ident_name(&part);
+                            tks.push(text_token(TokenKind::Ident, &part));
+                        }
+                        Rule::ident => {
+                            arm.params.push(ident_name(&part));
+                            tks.push(text_token(TokenKind::Ident, &part));
+                        }
+                        Rule::blank => tks.extend(blank_tokens(&part)),
+                        _ => panic!("invalid parsing rule: {}", part),
+                    }
+                }
+            }
+            Rule::arrow => tks.push(text_token(TokenKind::Symbol, &cur)),
+            Rule::blank => tks.extend(blank_tokens(&cur)),
+            Rule::exp => {
+                let (exp, mut exp_tks, spans) = build_lambda(cur, options)?;
+                tks.append(&mut exp_tks);
+                arm.exp = exp;
+                arm.spans = spans;
+            }
+            _ => panic!("invalid parsing rule: {}", cur),
+        }
+    }
+    Ok(arm)
+}
+
+/// Desugar `match e { Nil => a, Cons x xs => b }` into
+/// `caseList e a (\x xs. b)` by the data type of the constructors.
+fn build_match(
+    tt: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<(Exp<String>, Vec<Token>, SpanMap), Error> {
+    let span = tt.as_span();
+    let error = |range: Range<usize>, message: String| {
+        Error::ParseError(vec![ParseError::new(span.get_input(), range, message)])
+    };
+    let mut tks = vec![];
+    let mut scrutinee = None;
+    let mut arms = vec![];
+    for cur in tt.into_inner() {
+        match cur.as_rule() {
+            Rule::match_kw => tks.push(text_token(TokenKind::Keyword, &cur)),
+            Rule::lbrace | Rule::rbrace | Rule::comma => {
+                tks.push(text_token(TokenKind::Symbol, &cur))
+            }
+            Rule::blank => tks.extend(blank_tokens(&cur)),
+            Rule::exp => {
+                let (exp, mut exp_tks, spans) = build_lambda(cur, options)?;
+                tks.append(&mut exp_tks);
+                scrutinee = Some((exp, spans));
+            }
+            Rule::arm => arms.push(build_arm(cur, &mut tks, options)?),
+            _ => panic!("invalid parsing rule: {}", cur),
+        }
+    }
+    let (scrutinee, scrutinee_spans) = scrutinee.unwrap();
+
+    // `_` without fields matches the other constructors
+    let is_default = |arm: &Arm| arm.constructor == "_" && arm.params.is_empty();
+    let first = arms.iter().find(|arm| !is_default(arm)).ok_or_else(|| {
+        let message = String::from("expected an arm of a constructor");
+        error(span.start()..span.end(), message)
+    })?;
+    let datatype = options.datatype(&first.constructor).ok_or_else(|| {
+        let message = format!("unknown constructor `{}`", first.constructor);
+        error(first.range.clone(), message)
+    })?;
+
+    let mut cases: Vec<Option<(Exp<String>, SpanMap)>> = vec![None; datatype.constructors.len()];
+    let mut default = None;
+    for arm in arms {
+        if is_default(&arm) {
+            if default.is_some() {
+                return Err(error(arm.range, String::from("duplicate arm of `_`")));
+            }
+            default = Some((arm.exp, arm.spans));
+            continue;
+        }
+        // the same data type may be known by qualified and unqualified names
+        let position = options.datatype(&arm.constructor).and_then(|d| {
+            let i = d
+                .constructors
+                .iter()
+                .position(|c| c.name == arm.constructor);
+            i.filter(|_| d.eliminator == datatype.eliminator)
+        });
+        let Some(i) = position else {
+            let message = match options.datatype(&arm.constructor) {
+                Some(_) => format!(
+                    "`{}` isn't a constructor of `{}`",
+                    arm.constructor, datatype.name
+                ),
+                None => format!("unknown constructor `{}`", arm.constructor),
+            };
+            return Err(error(arm.range, message));
+        };
+        let fields = datatype.constructors[i].fields.len();
+        if arm.params.len() != fields {
+            let message = format!(
+                "`{}` has {} fields, found {}",
+                arm.constructor,
+                fields,
+                arm.params.len()
+            );
+            return Err(error(arm.range, message));
+        }
+        if cases[i].is_some() {
+            let message = format!("duplicate arm of `{}`", arm.constructor);
+            return Err(error(arm.range, message));
+        }
+        let (mut exp, mut spans) = (arm.exp, arm.spans);
+        for param in arm.params.into_iter().rev() {
+            exp = builder::abs(param, exp);
+            spans = prefix_spans(spans, &[Branch::AbsBody]);
+        }
+        cases[i] = Some((exp, spans));
+    }
+
+    let mut parts = vec![(
+        builder::unbounded_var(datatype.eliminator.clone()),
+        SpanMap::new(),
+    )];
+    parts.push((scrutinee, scrutinee_spans));
+    for (c, case) in datatype.constructors.iter().zip(cases) {
+        let case = match (case, &default) {
+            (Some(case), _) => case,
+            // the fields are ignored, so the abstractions bind nothing
+            (None, Some((exp, spans))) => {
+                let mut exp = exp.clone();
+                let mut spans = spans.clone();
+                for _ in &c.fields {
+                    exp = Exp::Abs(Ident(String::from("_"), 0), Box::new(exp));
+                    spans = prefix_spans(spans, &[Branch::AbsBody]);
+                }
+                (exp, spans)
+            }
+            (None, None) => {
+                let message = format!("missing arm of `{}`", c.name);
+                return Err(error(span.start()..span.end(), message));
+            }
+        };
+        parts.push(case);
+    }
+
+    // the k-th part is the body of the application nested in (n - k)
+    // functions, and the applications introduced by desugaring have no span
+    let n = parts.len();
+    let mut spans = HashMap::from([(Path::default(), span.start()..span.end())]);
+    let mut exprs = vec![];
+    for (k, (exp, part)) in parts.into_iter().enumerate() {
+        let mut branches = vec![Branch::AppFunc; n - 1 - k];
+        if k > 0 {
+            branches.push(Branch::AppBody);
+        }
+        spans.extend(prefix_spans(part, &branches));
+        exprs.push(exp);
+    }
+    Ok((builder::app(exprs), tks, spans))
+}
+
+type Operand = (Exp<String>, SpanMap, Range<usize>);
+
+/// Resolve `a0 op1 a1 op2 a2 ...` into applications of operators' definitions
+/// by their precedences and associativities.
+fn resolve_infix(
+    operands: Vec<Operand>,
+    ops: Vec<Pair<Rule>>,
+    options: &ParseOptions,
+) -> Result<(Exp<String>, SpanMap), Error> {
+    let reduce = |out: &mut Vec<Operand>, (fixity, op): (Fixity, Pair<Rule>)| {
+        let (b, b_spans, b_range) = out.pop().unwrap();
+        let (a, a_spans, a_range) = out.pop().unwrap();
+        let range = a_range.start..b_range.end;
+        // the partial application of the operator has no span
+        let mut spans = prefix_spans(a_spans, &[Branch::AppFunc, Branch::AppBody]);
+        spans.extend(prefix_spans(b_spans, &[Branch::AppBody]));
+        spans.insert(
+            Path(vec![Branch::AppFunc, Branch::AppFunc]),
+            op.as_span().start()..op.as_span().end(),
+        );
+        spans.insert(Path::default(), range.clone());
+        let exp = builder::app(vec![builder::unbounded_var(fixity.name), a, b]);
+        out.push((exp, spans, range));
+    };
+
+    let mut operands = operands.into_iter();
+    let mut out = vec![operands.next().unwrap()];
+    let mut stack: Vec<(Fixity, Pair<Rule>)> = vec![];
+    for (op, operand) in ops.into_iter().zip(operands) {
+        let fixity = options.fixity(op.as_str());
+        while let Some((top, top_op)) = stack.last() {
+            if top.precedence > fixity.precedence
+                || top.precedence == fixity.precedence
+                    && top.assoc == Assoc::Left
+                    && fixity.assoc == Assoc::Left
+            {
+                reduce(&mut out, stack.pop().unwrap());
+            } else if top.precedence == fixity.precedence
+                && !(top.assoc == Assoc::Right && fixity.assoc == Assoc::Right)
+            {
+                let span = op.as_span();
+                let message = format!(
+                    "cannot mix `{}` ({:?}, {}) and `{}` ({:?}, {}) without parentheses",
+                    top_op.as_str(),
+                    top.assoc,
+                    top.precedence,
+                    op.as_str(),
+                    fixity.assoc,
+                    fixity.precedence
+                );
+                let range = span.start()..span.end();
+                return Err(Error::ParseError(vec![ParseError {
+                    help: Some(String::from("add parentheses to group the operands")),
+                    ..ParseError::new(span.get_input(), range, message)
+                }]));
+            } else {
+                break;
+            }
+        }
+        stack.push((fixity, op));
+        out.push(operand);
+    }
+    while let Some(top) = stack.pop() {
+        reduce(&mut out, top);
+    }
+    let (exp, spans, _) = out.pop().unwrap();
+    Ok((exp, spans))
+}
+
+fn build_def(
+    tt: Pair<Rule>,
+    options: &ParseOptions,
+) -> Result<(String, Exp<String>, Vec<Token>, SpanMap), Error> {
     if let Rule::def = tt.as_rule() {
         let mut it = tt.into_inner();
-        let ident = it.next().unwrap().as_span().as_str().to_string();
+        let cur = it.next().unwrap();
+        let ident = ident_name(&cur);
 
-        let mut tks = vec![Token::Ident(ident.clone())];
+        let mut tks = vec![text_token(TokenKind::Ident, &cur)];
 
         let mut cur = it.next().unwrap();
         if let Rule::blank = cur.as_rule() {
-            tks.push(Token::Blank(cur.as_span().as_str().to_string()));
+            tks.extend(blank_tokens(&cur));
             cur = it.next().unwrap();
         }
         if let Rule::eq = cur.as_rule() {
-            tks.push(Token::Eq);
+            tks.push(token(TokenKind::Eq, cur.as_span()));
             cur = it.next().unwrap();
         } else {
             panic!("invalid parsing rule: {}", cur)
         }
         if let Rule::blank = cur.as_rule() {
-            tks.push(Token::Blank(cur.as_span().as_str().to_string()));
+            tks.extend(blank_tokens(&cur));
             cur = it.next().unwrap();
         }
         if let Rule::exp = cur.as_rule() {
-            let (exp, mut exp_tks) = build_lambda(cur)?;
+            let (exp, mut exp_tks, spans) = build_lambda(cur, options)?;
             tks.append(&mut exp_tks);
-            return Ok((ident, exp, tks));
+            return Ok((ident, exp, tks, spans));
         }
         panic!("invalid parsing rule: {}", cur)
     }
     panic!("invalid parsing rule: {}", tt)
 }
 
+fn build_fixity(tt: Pair<Rule>) -> (String, Fixity, Vec<Token>) {
+    let mut assoc = Assoc::None;
+    let mut precedence = 0;
+    let mut op = String::new();
+    let mut name = String::new();
+    let mut tks = vec![];
+    for cur in tt.into_inner() {
+        match cur.as_rule() {
+            Rule::assoc => {
+                assoc = match cur.as_str() {
+                    "infixl" => Assoc::Left,
+                    "infixr" => Assoc::Right,
+                    _ => Assoc::None,
+                };
+                tks.push(text_token(TokenKind::Keyword, &cur));
+            }
+            Rule::precedence => {
+                precedence = cur.as_str().parse().unwrap();
+                tks.push(text_token(TokenKind::Number, &cur));
+            }
+            Rule::op => {
+                op = cur.as_str().to_string();
+                tks.push(text_token(TokenKind::Operator, &cur));
+            }
+            Rule::ident => {
+                name = ident_name(&cur);
+                tks.push(text_token(TokenKind::Ident, &cur));
+            }
+            Rule::eq => tks.push(token(TokenKind::Eq, cur.as_span())),
+            Rule::blank => tks.extend(blank_tokens(&cur)),
+            _ => panic!("invalid parsing rule: {}", cur),
+        }
+    }
+    let fixity = Fixity {
+        assoc,
+        precedence,
+        name,
+    };
+    (op, fixity, tks)
+}
+
+fn build_data(tt: Pair<Rule>) -> (DataType, Vec<Token>) {
+    let mut name = String::new();
+    let mut encoding = data::Encoding::default();
+    let mut constructors = vec![];
+    let mut tks = vec![];
+    for cur in tt.into_inner() {
+        match cur.as_rule() {
+            Rule::data_kw => tks.push(text_token(TokenKind::Keyword, &cur)),
+            Rule::data_encoding => {
+                if cur.as_str() == "church" {
+                    encoding = data::Encoding::Church;
+                }
+                tks.push(text_token(TokenKind::Keyword, &cur));
+            }
+            Rule::ident => {
+                name = ident_name(&cur);
+                tks.push(text_token(TokenKind::Ident, &cur));
+            }
+            Rule::eq => tks.push(token(TokenKind::Eq, cur.as_span())),
+            Rule::bar => tks.push(text_token(TokenKind::Symbol, &cur)),
+            Rule::blank => tks.extend(blank_tokens(&cur)),
+            Rule::constructor => {
+                let mut constructor = Constructor {
+                    name: String::new(),
+                    fields: vec![],
+                };
+                for part in cur.into_inner() {
+                    match part.as_rule() {
+                        Rule::ident => {
+                            constructor.name = ident_name(&part);
+                            tks.push(text_token(TokenKind::Ident, &part));
+                        }
+                        Rule::field => {
+                            let mut field = Field {
+                                name: String::new(),
+                                recursive: false,
+                            };
+                            for (k, p) in part.into_inner().enumerate() {
+                                match p.as_rule() {
+                                    Rule::ident if k == 0 => field.name = ident_name(&p),
+                                    Rule::ident => field.recursive = ident_name(&p) == name,
+                                    _ => {}
+                                }
+                                let kind = match p.as_rule() {
+                                    Rule::colon => TokenKind::Symbol,
+                                    _ => TokenKind::Ident,
+                                };
+                                tks.push(text_token(kind, &p));
+                            }
+                            constructor.fields.push(field);
+                        }
+                        Rule::blank => tks.extend(blank_tokens(&part)),
+                        _ => panic!("invalid parsing rule: {}", part),
+                    }
+                }
+                constructors.push(constructor);
+            }
+            _ => panic!("invalid parsing rule: {}", cur),
+        }
+    }
+    (DataType::new(name, encoding, constructors), tks)
+}
+
+fn build_import(tt: Pair<Rule>) -> (Import, Vec<Token>) {
+    let span = tt.as_span().start()..tt.as_span().end();
+    let mut path = String::new();
+    let mut alias = None;
+    let mut tks = vec![];
+    for cur in tt.into_inner() {
+        match cur.as_rule() {
+            Rule::import_kw | Rule::as_kw => tks.push(text_token(TokenKind::Keyword, &cur)),
+            Rule::string => {
+                path = cur.as_str().trim_matches('"').to_string();
+                tks.push(text_token(TokenKind::Str, &cur));
+            }
+            Rule::ident => {
+                alias = Some(ident_name(&cur));
+                tks.push(text_token(TokenKind::Ident, &cur));
+            }
+            Rule::blank => tks.extend(blank_tokens(&cur)),
+            _ => panic!("invalid parsing rule: {}", cur),
+        }
+    }
+    let alias = alias.unwrap_or_else(|| Import::default_alias(&path));
+    (Import { path, alias, span }, tks)
+}
+
+/// Associativity of an infix operator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assoc {
+    /// `infixl`, `a + b + c` is `(a + b) + c`
+    Left,
+    /// `infixr`, `a + b + c` is `a + (b + c)`
+    Right,
+    /// `infix`, `a + b + c` is an error
+    None,
+}
+
+/// Fixity of an infix operator, declared like `infixl 6 + = Plus` in files.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixity {
+    /// Associativity
+    pub assoc: Assoc,
+    /// Precedence from 0 (loosest) to 9 (tightest)
+    pub precedence: u8,
+    /// Name of the definition that the operator stands for
+    pub name: String,
+}
+
+/// Options of parsing.
+///
+/// The `parse_*` functions of this module use the default options, and
+/// the methods of the same names parse with the given options.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::{lambda, numeral::Encoding, parser::ParseOptions};
+/// let options = ParseOptions {
+///     numeral: Encoding::Scott,
+///     ..Default::default()
+/// };
+/// let (one, _, _) = options.parse_exp_with_spans("1").unwrap();
+/// assert_eq!(one, lambda!(z. s. s (z. s. z)));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Encoding of number literals like `42`
+    pub numeral: numeral::Encoding,
+    /// Keep number and string literals native, i. e. as free variables
+    /// named like `42` and `"abc"`, for [`ExpExt`](crate::applied::ExpExt).
+    /// String literals are errors otherwise.
+    pub native_literals: bool,
+    /// Fixities of infix operators, keyed by operators like `+`.
+    ///
+    /// Undeclared operators are `infixl 9`, standing for the identifier of
+    /// the same name, e. g. `` `+` ``.
+    pub fixities: HashMap<String, Fixity>,
+    /// Data types whose constructors can be matched by `match`, where later
+    /// ones win for constructors of the same names.
+    pub datatypes: Vec<DataType>,
+}
+
+impl ParseOptions {
+    /// The data type of the constructor `name`.
+    pub fn datatype(&self, constructor: &str) -> Option<&DataType> {
+        self.datatypes
+            .iter()
+            .rev()
+            .find(|datatype| datatype.constructor(constructor).is_some())
+    }
+    /// Fixity of the operator `op`.
+    pub fn fixity(&self, op: &str) -> Fixity {
+        self.fixities.get(op).cloned().unwrap_or_else(|| Fixity {
+            assoc: Assoc::Left,
+            precedence: 9,
+            name: op.to_string(),
+        })
+    }
+    /// See [`parse_exp_with_spans`].
+    pub fn parse_exp_with_spans(
+        &self,
+        lambda: &str,
+    ) -> Result<(Exp<String>, Vec<Token>, SpanMap), Error> {
+        let exp = LambdaParser::parse(Rule::exp, lambda)
+            .map_err(|e| Error::ParseError(vec![pest_error(lambda, 0, e)]))?
+            .next()
+            .unwrap();
+        build_lambda(exp, self)
+    }
+    /// See [`parse_def`].
+    pub fn parse_def(&self, lambda: &str) -> Result<(String, Exp<String>, Vec<Token>), Error> {
+        let def = LambdaParser::parse(Rule::def, lambda)
+            .map_err(|e| Error::ParseError(vec![pest_error(lambda, 0, e)]))?
+            .next()
+            .unwrap();
+        let (ident, exp, tks, _) = build_def(def, self)?;
+        Ok((ident, exp, tks))
+    }
+    /// See [`parse_file`].
+    #[allow(clippy::type_complexity)]
+    pub fn parse_file(
+        &self,
+        lambda: &str,
+    ) -> Result<(HashMap<String, Exp<String>>, Vec<Token>), Error> {
+        let module = self.parse_module(lambda)?;
+        Ok((module.to_map(), module.tokens))
+    }
+    /// See [`parse_module`].
+    pub fn parse_module(&self, lambda: &str) -> Result<Module, Error> {
+        let lines = LambdaParser::parse(Rule::file, lambda)
+            .map_err(|e| Error::ParseError(line_errors(lambda, e)))?
+            .next()
+            .unwrap()
+            .into_inner();
+
+        // fixities and data types are declared for the whole file, so
+        // collect them first
+        let mut options = self.clone();
+        let fixities = file_fixities(lines.clone());
+        options.fixities.extend(fixities.clone());
+        let datatypes = file_datatypes(lines.clone());
+        options.datatypes.extend(datatypes.clone());
+        let options = &options;
+
+        let mut module = Module {
+            fixities,
+            datatypes,
+            ..Default::default()
+        };
+        let tks = &mut module.tokens;
+        // comment lines above the current line
+        let mut pending_doc: Vec<String> = vec![];
+        let mut def_lines: HashMap<String, usize> = HashMap::new();
+        // errors of well-formed statements, e. g. mixing operators
+        let mut errors = vec![];
+
+        for rule in lines.into_iter() {
+            if let Rule::EOI = rule.as_rule() {
+                break;
+            } else if let Rule::line = rule.as_rule() {
+                let mut leading = vec![];
+                let mut trailing = vec![];
+                let mut defs = vec![];
+                let mut is_statement = false;
+                for cur in rule.into_inner() {
+                    match cur.as_rule() {
+                        Rule::indent => {
+                            if !cur.as_str().is_empty() {
+                                tks.push(text_token(TokenKind::Blank, &cur));
+                            }
+                        }
+                        Rule::inline_blank | Rule::blank => {
+                            let comments = cur
+                                .clone()
+                                .into_inner()
+                                .filter(|c| matches!(c.as_rule(), Rule::com | Rule::block_com))
+                                .map(|c| comment_text(c.as_str()));
+                            if is_statement {
+                                trailing.extend(comments);
+                            } else {
+                                leading.extend(comments);
+                            }
+                            tks.extend(blank_tokens(&cur));
+                        }
+                        Rule::def => {
+                            let span = cur.as_span().start()..cur.as_span().end();
+                            let (name, exp, mut def_tks, spans) = match build_def(cur, options) {
+                                Ok(def) => def,
+                                Err(Error::ParseError(mut errs)) => {
+                                    errors.append(&mut errs);
+                                    is_statement = true;
+                                    continue;
+                                }
+                                Err(e) => return Err(e),
+                            };
+                            tks.append(&mut def_tks);
+                            defs.push(Definition {
+                                name,
+                                exp,
+                                doc: None,
+                                span,
+                                spans,
+                            });
+                            is_statement = true;
+                        }
+                        Rule::data => {
+                            let span = cur.as_span().start()..cur.as_span().end();
+                            let (datatype, mut data_tks) = build_data(cur);
+                            tks.append(&mut data_tks);
+                            for (name, exp) in datatype.definitions() {
+                                defs.push(Definition {
+                                    name,
+                                    exp,
+                                    doc: None,
+                                    span: span.clone(),
+                                    spans: SpanMap::new(),
+                                });
+                            }
+                            is_statement = true;
+                        }
+                        Rule::fixity => {
+                            let (_, _, mut fixity_tks) = build_fixity(cur);
+                            tks.append(&mut fixity_tks);
+                            is_statement = true;
+                        }
+                        Rule::import => {
+                            let (import, mut import_tks) = build_import(cur);
+                            tks.append(&mut import_tks);
+                            module.imports.push(import);
+                            is_statement = true;
+                        }
+                        Rule::com => {
+                            trailing.push(comment_text(cur.as_str()));
+                            tks.push(text_token(TokenKind::Comment, &cur));
+                        }
+                        _ => panic!("invalid parsing rule: {}", cur),
+                    }
+                }
+
+                if !is_statement {
+                    // a line of comments documents the next definition, and
+                    // an empty line breaks the documentation
+                    leading.append(&mut trailing);
+                    if leading.is_empty() {
+                        pending_doc.clear();
+                    }
+                    pending_doc.append(&mut leading);
+                    continue;
+                }
+                let mut doc = std::mem::take(&mut pending_doc);
+                doc.append(&mut leading);
+                if doc.is_empty() {
+                    doc = trailing;
+                }
+                for mut def in defs {
+                    def.doc = (!doc.is_empty()).then(|| doc.join("\n"));
+
+                    let line = line_of(lambda, def.span.start);
+                    if let Some(previous_line) = def_lines.insert(def.name.clone(), line) {
+                        module.warnings.push(Warning::Redefined {
+                            name: def.name.clone(),
+                            span: def.span.clone(),
+                            line,
+                            previous_line,
+                        });
+                    }
+                    module.definitions.push(def);
+                }
+            } else if let Rule::newline = rule.as_rule() {
+                tks.push(text_token(TokenKind::LineFeed, &rule));
+            } else {
+                panic!("invalid parsing rule: {}", rule)
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(Error::ParseError(errors));
+        }
+        Ok(module)
+    }
+}
+
 /// Parse a lambda expression. e. g. `\f. (\x. f (x x)) \x. f (x x)`.
 ///
+/// Natural numbers like `42` are Church numerals, see [`ParseOptions`] for
+/// other encodings.
+///
 /// Return its expression object and token list.
 pub fn parse_exp(lambda: &str) -> Result<(Exp<String>, Vec<Token>), Error> {
-    let exp = LambdaParser::parse(Rule::exp, lambda)
-        .map_err(|e| Error::ParseError(e.to_string()))?
-        .next()
-        .unwrap();
-    build_lambda(exp)
+    let (exp, tks, _) = parse_exp_with_spans(lambda)?;
+    Ok((exp, tks))
+}
+
+/// Parse a lambda expression like [`parse_exp`], additionally returning the
+/// byte range of every subexpression.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::{parser::parse_exp_with_spans, Branch, Path};
+/// let s = r"\x. (x y) z";
+/// let (exp, _, spans) = parse_exp_with_spans(s).unwrap();
+/// let path = Path(vec![Branch::AbsBody, Branch::AppFunc]);
+/// assert_eq!(&s[spans[&path].clone()], "x y");
+/// assert_eq!(exp.get(&path).unwrap().to_string(), "x y");
+/// ```
+pub fn parse_exp_with_spans(lambda: &str) -> Result<(Exp<String>, Vec<Token>, SpanMap), Error> {
+    ParseOptions::default().parse_exp_with_spans(lambda)
 }
 
 /// Parse a single line of definition of lambda exp. e. g. `Y = \f. (\x. f (x x)) \x. f (x x)`.
 ///
 /// Return its idetifier, expression object and token list.
 pub fn parse_def(lambda: &str) -> Result<(String, Exp<String>, Vec<Token>), Error> {
-    let def = LambdaParser::parse(Rule::def, lambda)
-        .map_err(|e| Error::ParseError(e.to_string()))?
-        .next()
-        .unwrap();
-    build_def(def)
+    ParseOptions::default().parse_def(lambda)
 }
 
 /// Parse multiple definitions of lambda expression one by a line.
@@ -216,66 +1291,177 @@ pub fn parse_def(lambda: &str) -> Result<(String, Exp<String>, Vec<Token>), Erro
 /// Exp = \n. \m. n (Mul m) One
 /// ```
 ///
-/// Return a map from idetifier to expression object, and the whole content's token list.
+/// Infix operators can be declared anywhere in the file with their
+/// associativity, precedence and the definitions they stand for:
+///
+/// ```plain
+/// infixl 6 + = Plus
+/// infixl 7 * = Mul
+/// infixr 8 ^ = Exp
+/// Poly = \x. x ^ Two + Two * x + One
+/// ```
+///
+/// A long definition can be wrapped: a line continues the definition if it's
+/// indented deeper than the definition, or if it's indented or starts with `)`
+/// within unclosed parentheses. Besides `// ...`, comments can be written as
+/// `/* ... */`. Tabs and CRLF line endings are accepted.
+///
+/// ```plain
+/// Fact = Y \f n.
+///     If (IsZero n) 1 /* base case */
+///         (Mul n (f (Pred n)))
+/// ```
+///
+/// Data types can be declared with their constructors, which defines the
+/// constructors and an eliminator, see [`data`](crate::data). Values are taken
+/// apart by `match`, whose arms bind the fields of the constructors, and `_`
+/// matches the rest:
+///
+/// ```plain
+/// data List = Nil | Cons head tail:List
+/// Head = \xs. match xs { Cons x rest => x, _ => Nil }
+/// ```
+///
+/// Return a map from idetifier to expression object, and the whole content's token list,
+/// which reproduces the content byte for byte.
 ///
 /// For multiple definitions of the same variable, the last one will be adopted.
+/// See [`parse_module`] to keep the order, doc comments and redefinitions.
+///
+/// On failure, every bad line is reported in [`Error::ParseError`].
+#[allow(clippy::type_complexity)]
 pub fn parse_file(lambda: &str) -> Result<(HashMap<String, Exp<String>>, Vec<Token>), Error> {
+    ParseOptions::default().parse_file(lambda)
+}
+
+/// Parse a definition file like [`parse_file`], keeping the order, doc
+/// comments and spans of the definitions.
+///
+/// Comment lines right above a definition, and comments before it on the
+/// same line, document it. Without them, comments after it on the same line
+/// document it instead.
+///
+/// # Example
+///
+/// ```
+/// # use lamcalc::parser::parse_module;
+/// let module = parse_module("// identity\nI = \\x. x\nK = \\x y. x // constant").unwrap();
+/// let docs: Vec<_> = module.visible().map(|def| def.doc.as_deref()).collect();
+/// assert_eq!(docs, [Some("identity"), Some("constant")]);
+/// ```
+pub fn parse_module(lambda: &str) -> Result<Module, Error> {
+    ParseOptions::default().parse_module(lambda)
+}
+
+fn file_fixities(lines: Pairs<Rule>) -> HashMap<String, Fixity> {
+    lines
+        .filter(|line| line.as_rule() == Rule::line)
+        .flat_map(|line| line.into_inner())
+        .filter(|cur| cur.as_rule() == Rule::fixity)
+        .map(|cur| {
+            let (op, fixity, _) = build_fixity(cur);
+            (op, fixity)
+        })
+        .collect()
+}
+
+fn file_datatypes(lines: Pairs<Rule>) -> Vec<DataType> {
+    lines
+        .filter(|line| line.as_rule() == Rule::line)
+        .flat_map(|line| line.into_inner())
+        .filter(|cur| cur.as_rule() == Rule::data)
+        .map(|cur| build_data(cur).0)
+        .collect()
+}
+
+/// Parse the imports of a file like `import "bool.lambda" as Bool`, e. g. to
+/// load them before parsing the file with their operators and data types.
+pub fn parse_imports(lambda: &str) -> Result<Vec<Import>, Error> {
     let lines = LambdaParser::parse(Rule::file, lambda)
-        .map_err(|e| Error::ParseError(e.to_string()))?
+        .map_err(|e| Error::ParseError(line_errors(lambda, e)))?
         .next()
         .unwrap()
         .into_inner();
+    Ok(lines
+        .filter(|line| line.as_rule() == Rule::line)
+        .flat_map(|line| line.into_inner())
+        .filter(|cur| cur.as_rule() == Rule::import)
+        .map(|cur| build_import(cur).0)
+        .collect())
+}
 
-    let mut map = HashMap::new();
-    let mut tks = vec![];
+/// Parse the fixity declarations of a file like `infixl 6 + = Plus`, which
+/// can be used to print the definitions back infix, see
+/// [`PrintOptions`](crate::pretty::PrintOptions).
+///
+/// For multiple declarations of the same operator, the last one will be adopted.
+pub fn parse_fixities(lambda: &str) -> Result<HashMap<String, Fixity>, Error> {
+    let lines = LambdaParser::parse(Rule::file, lambda)
+        .map_err(|e| Error::ParseError(line_errors(lambda, e)))?
+        .next()
+        .unwrap()
+        .into_inner();
+    Ok(file_fixities(lines))
+}
 
-    for rule in lines.into_iter() {
-        if let Rule::EOI = rule.as_rule() {
-            break;
-        } else if let Rule::line = rule.as_rule() {
-            let mut it = rule.into_inner();
-            let mut cur = match it.next() {
-                Some(nex) => nex,
-                None => continue,
+fn build_de_bruijn(tt: Pair<Rule>, depth: u32) -> Result<Exp<()>, Error> {
+    match tt.as_rule() {
+        Rule::db_exp => build_de_bruijn(tt.into_inner().next().unwrap(), depth),
+        Rule::db_index => {
+            let span = tt.as_span();
+            let error = |message| {
+                let input = span.get_input();
+                Error::ParseError(vec![ParseError::new(
+                    input,
+                    span.start()..span.end(),
+                    message,
+                )])
             };
-            if let Rule::blank = cur.as_rule() {
-                tks.push(Token::Blank(cur.as_span().as_str().to_string()));
-                cur = match it.next() {
-                    Some(nex) => nex,
-                    None => continue,
-                }
+            let code: u32 = tt
+                .as_str()
+                .parse()
+                .map_err(|e| error(format!("invalid index {}: {}", tt.as_str(), e)))?;
+            if code > depth {
+                return Err(error(format!(
+                    "index {} exceeds the number of enclosing abstractions ({})",
+                    code, depth
+                )));
             }
-
-            if let Rule::def = cur.as_rule() {
-                let (ident, lam, mut def_tks) = build_def(cur)?;
-                tks.append(&mut def_tks);
-                map.insert(ident, lam);
-                cur = match it.next() {
-                    Some(nex) => nex,
-                    None => continue,
-                };
-                if let Rule::blank = cur.as_rule() {
-                    tks.push(Token::Blank(cur.as_span().as_str().to_string()));
-                    cur = match it.next() {
-                        Some(nex) => nex,
-                        None => continue,
-                    }
-                }
-            }
-
-            if let Rule::com = cur.as_rule() {
-                tks.push(Token::Comment(cur.as_span().as_str().to_string()));
-                continue;
-            }
-            panic!("invalid parsing rule: {}", cur)
-        } else if let Rule::newline = rule.as_rule() {
-            tks.push(Token::LineFeed);
-        } else {
-            panic!("invalid parsing rule: {}", rule)
+            Ok(Exp::Var(Ident((), code)))
+        }
+        Rule::db_abs => {
+            let body = build_de_bruijn(tt.into_inner().next().unwrap(), depth + 1)?;
+            Ok(Exp::Abs(Ident((), 0), Box::new(body)))
+        }
+        Rule::db_app => {
+            let mut it = tt.into_inner();
+            let func = build_de_bruijn(it.next().unwrap(), depth)?;
+            let body = build_de_bruijn(it.next().unwrap(), depth)?;
+            Ok(Exp::App(Box::new(func), Box::new(body)))
         }
+        _ => panic!("invalid parsing rule: {}", tt),
     }
+}
 
-    Ok((map, tks))
+/// Parse a nameless expression in the format printed by `Exp<()>`,
+/// e. g. `λλλ[2]([[3](2)](1))`. Both `λ` and `\` are accepted.
+///
+/// Use [`Exp::to_named_exp`] to get a readable expression.
+///
+/// ```
+/// # use lamcalc::{lambda, parser::parse_de_bruijn};
+/// let suc = lambda!(n. s. z. s (n s z)).purify();
+/// assert_eq!(parse_de_bruijn(&suc.to_string()).unwrap(), suc);
+/// ```
+pub fn parse_de_bruijn(lambda: &str) -> Result<Exp<()>, Error> {
+    let exp = LambdaParser::parse(Rule::de_bruijn, lambda)
+        .map_err(|e| Error::ParseError(vec![pest_error(lambda, 0, e)]))?
+        .next()
+        .unwrap()
+        .into_inner()
+        .next()
+        .unwrap();
+    build_de_bruijn(exp, 0)
 }
 
 #[cfg(test)]
@@ -286,15 +1472,25 @@ mod tests {
     fn tks_str(tks: &[Token]) -> String {
         let mut res = String::new();
         for tk in tks {
-            res.push_str(match tk {
-                Token::DotSym => ".",
-                Token::LPar => "(",
-                Token::RPar => ")",
-                Token::Eq => "=",
-                Token::LineFeed => "\n",
-
-                Token::LamSym(s) | Token::Ident(s) | Token::Comment(s) | Token::Blank(s) => s,
+            assert_eq!(tk.span.start, res.len());
+            res.push_str(match &tk.kind {
+                TokenKind::DotSym => ".",
+                TokenKind::LPar => "(",
+                TokenKind::RPar => ")",
+                TokenKind::Eq => "=",
+
+                TokenKind::LineFeed(s)
+                | TokenKind::LamSym(s)
+                | TokenKind::Ident(s)
+                | TokenKind::Comment(s)
+                | TokenKind::Blank(s)
+                | TokenKind::Keyword(s)
+                | TokenKind::Number(s)
+                | TokenKind::Operator(s)
+                | TokenKind::Str(s)
+                | TokenKind::Symbol(s) => s,
             });
+            assert_eq!(tk.span.end, res.len());
         }
         res
     }
@@ -312,6 +1508,130 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn test_parse_exp_with_spans() -> Result<(), Error> {
+        let s = r"(\x. x) ( y  z ) \w. w";
+        let (exp, tks, spans) = parse_exp_with_spans(s)?;
+        assert_eq!(tks_str(&tks), s);
+
+        let path = |branches: &[Branch]| Path(branches.to_vec());
+        let text = |branches: &[Branch]| &s[spans[&path(branches)].clone()];
+        use Branch::*;
+        assert_eq!(text(&[]), s);
+        assert_eq!(text(&[AppFunc]), r"(\x. x) ( y  z )");
+        assert_eq!(text(&[AppFunc, AppFunc]), r"\x. x");
+        assert_eq!(text(&[AppFunc, AppFunc, AbsBody]), "x");
+        assert_eq!(text(&[AppFunc, AppBody]), "y  z");
+        assert_eq!(text(&[AppFunc, AppBody, AppBody]), "z");
+        assert_eq!(text(&[AppBody, AbsBody]), "w");
+
+        // every node has a span
+        let mut count = 0;
+        for p in spans.keys() {
+            assert!(exp.get(p).is_some());
+            count += 1;
+        }
+        assert_eq!(count, 9);
+
+        Ok(())
+    }
+    #[test]
+    fn test_sugar() -> Result<(), Error> {
+        let s = r"\x  y z. let w = x y in (let v = \u v. v in w v) z";
+        let (exp, tks, spans) = parse_exp_with_spans(s)?;
+        assert_eq!(tks_str(&tks), s);
+        assert_eq!(exp, lambda!(x. y. z. (w. (v. w v) (u. v. v) z) (x y)));
+        assert_eq!(&s[spans[&Path(vec![Branch::AbsBody])].clone()], &s[4..]);
+        let value = Path(
+            vec![Branch::AbsBody; 3]
+                .into_iter()
+                .chain([Branch::AppBody])
+                .collect(),
+        );
+        assert_eq!(&s[spans[&value].clone()], "x y");
+
+        assert_eq!(parse_exp(r"\x inn. inn x")?.0, lambda!(x. inn. inn x));
+        assert!(parse_exp(r"\x in. x").is_err());
+        assert!(parse_exp(r"let x = y in").is_err());
+
+        Ok(())
+    }
+    #[test]
+    fn test_numeral() -> Result<(), Error> {
+        let s = r"(\n f x. f (n f x)) 41";
+        let (mut exp, tks) = parse_exp(s)?;
+        assert_eq!(tks_str(&tks), s);
+        exp.simplify(false)?;
+        assert_eq!(exp, numeral::encode(42, numeral::Encoding::Church));
+
+        let options = ParseOptions {
+            numeral: numeral::Encoding::Binary,
+            ..Default::default()
+        };
+        let (exp, _, spans) = options.parse_exp_with_spans("(0 1) 6")?;
+        assert_eq!(
+            exp,
+            lambda!({numeral::encode(0, options.numeral)} {numeral::encode(1, options.numeral)} {numeral::encode(6, options.numeral)})
+        );
+        assert_eq!(spans.len(), 5);
+
+        assert!(parse_exp("99999999999999999999").is_err());
+        // unary numerals are bounded, binary ones aren't
+        let Err(Error::ParseError(errors)) = parse_exp("f 9999999999") else {
+            panic!("expect parse error")
+        };
+        assert_eq!(errors[0].span, 2..12);
+        assert!(parse_exp("256").is_ok());
+        let options = ParseOptions {
+            numeral: numeral::Encoding::Binary,
+            ..Default::default()
+        };
+        assert!(options.parse_exp_with_spans("9999999999").is_ok());
+
+        // string literals are only native
+        let s = r#"f "ab" 1"#;
+        let Err(Error::ParseError(errors)) = parse_exp(s) else {
+            panic!("expect parse error")
+        };
+        assert_eq!(errors[0].span, 2..6);
+        assert!(errors[0].help.is_some());
+        let options = ParseOptions {
+            native_literals: true,
+            ..Default::default()
+        };
+        let (exp, _, _) = options.parse_exp_with_spans(s)?;
+        assert_eq!(exp, lambda!(f (# "ab") (# 1)));
+        Ok(())
+    }
+    #[test]
+    fn test_ident() -> Result<(), Error> {
+        let s = r"\f' x₁ α. `+` (is-zero? f') (f' x₁) α";
+        let (exp, tks) = parse_exp(s)?;
+        assert_eq!(tks_str(&tks), s);
+        assert_eq!(
+            format!("{:#}", exp),
+            "λf'. λx₁. λα. ((+<0> (is-zero?<0> f'<3>)) (f'<3> x₁<2>)) α<1>"
+        );
+        assert_eq!(
+            exp,
+            lambda!("f'" "x₁" "α". "+" ("is-zero?" "f'") ("f'" "x₁") "α")
+        );
+
+        let (name, exp, tks) = parse_def(r"`==` = \`a b`. `a b`")?;
+        assert_eq!(name, "==");
+        assert_eq!(exp, lambda!("a b". "a b"));
+        assert_eq!(tks_str(&tks), r"`==` = \`a b`. `a b`");
+
+        let (exp, _) = parse_exp(r"\T. Bool.and T (List.Bool.not T)")?;
+        assert_eq!(exp, lambda!(T. "Bool.and" T ("List.Bool.not" T)));
+        let (exp, _) = parse_exp(r"\T.T")?;
+        assert_eq!(exp, lambda!(T. T));
+
+        assert!(parse_exp(r"\x-. x").is_err());
+        assert!(parse_exp("λ").is_err());
+        assert!(parse_exp("``").is_err());
+        Ok(())
+    }
+    #[test]
     fn test_parse_def() -> Result<(), Error> {
         let s = r"tt    =   \x. \y. x";
         let (name, tt, tks) = parse_def(s)?;
@@ -349,6 +1669,228 @@ mod tests {
         Ok(())
     }
     #[test]
+    fn test_infix() -> Result<(), Error> {
+        let lambda = r#"
+            infixl 6 + = Plus
+            infixl 7 * = Mul // tighter
+            infixr 5 :: = Cons
+            infix 4 == = Eq
+            Poly = \x. x * x + 2 * x + 1
+            List = 1 :: 2 :: Nil
+            Sum = (+) 1
+            Eq1 = Plus (Mul a b) c == a * b + c
+        "#;
+        let (res, tks) = parse_file(lambda)?;
+        assert_eq!(tks_str(&tks), lambda);
+        assert_eq!(res["Poly"], lambda!(x. Plus (Plus (Mul x x) (Mul 2 x)) 1));
+        assert_eq!(res["List"], lambda!(Cons 1 (Cons 2 Nil)));
+        assert_eq!(res["Sum"], lambda!(Plus 1));
+        assert_eq!(
+            res["Eq1"],
+            lambda!(Eq (Plus (Mul a b) c) (Plus (Mul a b) c))
+        );
+
+        // undeclared operators are infixl 9
+        let (exp, _) = parse_exp("a <> b <> c")?;
+        assert_eq!(exp, lambda!("<>" ("<>" a b) c));
+
+        let s = "f a + b";
+        let options = ParseOptions {
+            fixities: parse_fixities("infixl 6 + = Plus")?,
+            ..Default::default()
+        };
+        let (exp, _, spans) = options.parse_exp_with_spans(s)?;
+        assert_eq!(exp, lambda!(Plus (f a) b));
+        let span = |branches: &[Branch]| spans.get(&Path(branches.to_vec())).cloned();
+        assert_eq!(span(&[]), Some(0..7));
+        assert_eq!(span(&[Branch::AppFunc]), None);
+        assert_eq!(span(&[Branch::AppFunc, Branch::AppFunc]), Some(4..5));
+        assert_eq!(span(&[Branch::AppFunc, Branch::AppBody]), Some(0..3));
+        assert_eq!(span(&[Branch::AppBody]), Some(6..7));
+
+        let Err(Error::ParseError(errs)) = parse_file("infix 4 == = Eq\nX = a == b == c") else {
+            panic!("non-associative operators are chained")
+        };
+        assert_eq!((errs[0].line, errs[0].column), (2, 12));
+        assert!(parse_file("infixl 4 + = Plus\ninfixr 4 - = Minus\nX = a + b - c").is_err());
+
+        Ok(())
+    }
+    #[test]
+    fn test_deep_nesting() -> Result<(), Error> {
+        // operands are parsed once, otherwise it takes exponential time
+        let depth = 48;
+        let s = format!("{}x{}", "f (".repeat(depth), ")".repeat(depth));
+        let (exp, _) = parse_exp(&s)?;
+        let expected = (0..depth).fold(lambda!(x), |e, _| lambda!(f { e }));
+        assert_eq!(exp, expected);
+
+        let s = format!("{}a{}", "(a + ".repeat(depth), ")".repeat(depth));
+        assert!(parse_exp(&s).is_ok());
+        Ok(())
+    }
+    #[test]
+    fn test_multiline() -> Result<(), Error> {
+        let lambda = concat!(
+            "/* Factorial,\r\n",
+            " * wrapped */\r\n",
+            "Fact = Y \\f n.\r\n",
+            "\tIf (IsZero n)\r\n",
+            "\t\t1 // base case\r\n",
+            "\t\t(Mul n (f (Pred n)))\r\n",
+            "Pair = \\x y f. (f\r\n",
+            "  x /* first */\r\n",
+            ") y\r\n",
+            "Id = \\x. x\r\n",
+            "\r\n",
+        );
+        let (res, tks) = parse_file(lambda)?;
+        assert_eq!(tks_str(&tks), lambda);
+        assert_eq!(
+            res["Fact"],
+            lambda!(Y (f. n. If (IsZero n) 1 (Mul n (f (Pred n)))))
+        );
+        assert_eq!(res["Pair"], lambda!(x. y. f. f x y));
+        assert_eq!(res["Id"], lambda!(x.x));
+
+        // continuation lines must be indented deeper than the statement
+        let Err(Error::ParseError(errs)) =
+            parse_file("  I = \\x.\n  x\nK = (\\x y.\nx)\nS = \\x. x")
+        else {
+            panic!("expect parse error")
+        };
+        let lines: Vec<_> = errs.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [1, 2, 3, 4]);
+
+        let (exp, _) = parse_exp("(\\x.\n\tx)")?;
+        assert_eq!(exp, lambda!(x.x));
+        Ok(())
+    }
+    #[test]
+    fn test_data() -> Result<(), Error> {
+        let lambda = r"
+// lists of Church numerals
+data List = Nil | Cons head tail:List
+data church Nat = Z | S pred:Nat
+Sum = \xs. match xs {
+    Nil => 0,
+    Cons x rest => Plus x (Sum rest),
+}
+IsNil = \xs. match xs { Nil => T, _ => F }
+";
+        let module = parse_module(lambda)?;
+        assert_eq!(tks_str(&module.tokens), lambda);
+        let names: Vec<_> = module.visible().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["Nil", "Cons", "caseList", "Z", "S", "caseNat", "Sum", "IsNil"]
+        );
+        let cons = module.get("Cons").unwrap();
+        assert_eq!(cons.doc.as_deref(), Some("lists of Church numerals"));
+        assert_eq!(
+            &lambda[cons.span.clone()],
+            "data List = Nil | Cons head tail:List"
+        );
+        assert_eq!(module.datatypes[1].encoding, data::Encoding::Church);
+        assert_eq!(
+            module.get("S").unwrap().exp,
+            lambda!(pred. z. s. s (pred z s))
+        );
+
+        assert_eq!(
+            module.get("Sum").unwrap().exp,
+            lambda!(xs. caseList xs 0 (x. rest. Plus x (Sum rest)))
+        );
+        let is_nil = module.get("IsNil").unwrap();
+        assert_eq!(is_nil.exp, lambda!(xs. caseList xs T ("_". "_". F)));
+        let path = Path(vec![Branch::AbsBody, Branch::AppFunc, Branch::AppBody]);
+        assert_eq!(&lambda[is_nil.spans[&path].clone()], "T");
+
+        let lines = "data List = Nil | Cons head tail:List\n";
+        for (body, message) in [
+            ("match xs { Nil => a }", "missing arm of `Cons`"),
+            (
+                "match xs { Nil => a, Nil => b, _ => c }",
+                "duplicate arm of `Nil`",
+            ),
+            (
+                "match xs { Cons x => a, _ => b }",
+                "`Cons` has 2 fields, found 1",
+            ),
+            (
+                "match xs { Nil => a, Z => b }",
+                "`Z` isn't a constructor of `List`",
+            ),
+            ("match xs { W => a }", "unknown constructor `W`"),
+        ] {
+            let lambda = format!("{}data Nat = Z\nX = {}", lines, body);
+            let Err(Error::ParseError(errs)) = parse_module(&lambda) else {
+                panic!("expect parse error")
+            };
+            assert_eq!(errs[0].message, message);
+        }
+        assert!(parse_exp("match = 1").is_err());
+        Ok(())
+    }
+    #[test]
+    fn test_parse_de_bruijn() -> Result<(), Error> {
+        let y_comb = lambda!(f.(x. f (x x)) (x. f (x x))).purify();
+        assert_eq!(parse_de_bruijn(&y_comb.to_string())?, y_comb);
+        assert_eq!(parse_de_bruijn(r"\\[1](2)")?, lambda!(x. y. y x).purify());
+        assert_eq!(parse_de_bruijn("[λ1](0)")?.to_string(), "[λ1](0)");
+
+        assert!(parse_de_bruijn("λ2").is_err());
+        assert!(parse_de_bruijn("[λ1]0").is_err());
+        assert!(parse_de_bruijn("λ1 ").is_err());
+
+        Ok(())
+    }
+    #[test]
+    fn test_parse_error() {
+        let Err(Error::ParseError(errors)) = parse_exp(r"\x (y") else {
+            panic!("expect parse error")
+        };
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, 3..4);
+        assert_eq!((errors[0].line, errors[0].column), (1, 4));
+        assert!(errors[0].expected.contains(&String::from("`.`")));
+        assert!(errors[0].help.is_some());
+
+        let Err(Error::ParseError(errors)) = parse_exp("λx. x") else {
+            panic!("expect parse error")
+        };
+        assert_eq!(errors[0].span, 0..2);
+        assert_eq!(errors[0].help.as_deref(), Some("use `\\` for lambda"));
+
+        let Err(Error::ParseError(errors)) = parse_de_bruijn("λλ[1](3)") else {
+            panic!("expect parse error")
+        };
+        assert_eq!(errors[0].span, 8..9);
+    }
+    #[test]
+    fn test_parse_file_errors() {
+        let lambda = "I = \\x. x\nK = \\x. y. x\n\nS = (\\x. x\r\nT = \\x. \\y. x\n";
+        let Err(Error::ParseError(errors)) = parse_file(lambda) else {
+            panic!("expect parse error")
+        };
+        assert_eq!(errors.len(), 2);
+        assert_eq!((errors[0].line, errors[0].column), (2, 10));
+        assert_eq!(&lambda[errors[0].span.clone()], ".");
+        assert_eq!(errors[1].line, 4);
+        assert_eq!(errors[1].span.start, lambda.find("S = ").unwrap() + 10);
+        let message = Error::ParseError(errors).to_string();
+        assert_eq!(message.lines().count(), 3);
+        assert!(message.starts_with("error parsing\n2:10: "), "{}", message);
+
+        // errors of well-formed statements are collected as well
+        let lambda = "infix 4 == = Eq\nX = a == b == c\nI = \\x. x\nY = 99999999999999999999\n";
+        let Err(Error::ParseError(errors)) = parse_file(lambda) else {
+            panic!("expect parse error")
+        };
+        let lines: Vec<_> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, [2, 4]);
+    }
+    #[test]
     fn test_cjk() -> Result<(), Error> {
         let s = r"(\x. \y. x 即 是 y y 即 是 x) 色 空";
 
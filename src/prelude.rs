@@ -0,0 +1,193 @@
+//! Standard prelude of encodings shipped with the crate.
+//!
+//! The prelude is a definition file, see [`SOURCE`], of combinators,
+//! booleans, pairs, Church numerals with arithmetic, Scott-encoded lists,
+//! the fixed-point combinators `Y` and `Z`, and the self-interpreter `Eval`.
+//! Every definition is documented in the file.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{lambda, numeral::{decode, Encoding}, prelude};
+//! let env = prelude::environment();
+//! let mut e = lambda!(Sum (Map Succ (Cons 1 (Cons 2 Nil))));
+//! env.simplify(&mut e).unwrap();
+//! assert_eq!(decode(&e, Encoding::Church), Some(5));
+//!
+//! let mut e = lambda!({prelude::NOT} {prelude::TRUE});
+//! e.simplify(false).unwrap();
+//! assert_eq!(e, *prelude::FALSE);
+//! ```
+use crate::{
+    builder,
+    env::Environment,
+    module::Module,
+    parser::{self, ParseOptions},
+    Exp,
+};
+use std::sync::LazyLock;
+
+/// Source of the prelude.
+pub const SOURCE: &str = include_str!("./prelude.lambda");
+
+/// Name of the prelude when imported, e. g. `import "prelude.lambda"` in
+/// the definitions of the wasm `Calculator`.
+pub const FILE_NAME: &str = "prelude.lambda";
+
+static ENVIRONMENT: LazyLock<Environment> = LazyLock::new(environment);
+
+/// The prelude as a module.
+pub fn module() -> Module {
+    parser::parse_module(SOURCE).expect("the prelude parses")
+}
+
+/// Environment of the prelude definitions, expanding them eagerly.
+pub fn environment() -> Environment {
+    Environment::from_module(&module()).expect("the prelude is well-defined")
+}
+
+/// Options parsing expressions with the operators and data types of the
+/// prelude, e. g. `2 + 3` and `match xs { Nil => a, Cons x rest => b }`.
+pub fn parse_options() -> ParseOptions {
+    let module = module();
+    ParseOptions {
+        fixities: module.fixities,
+        datatypes: module.datatypes,
+        ..Default::default()
+    }
+}
+
+/// The closed expression of a prelude definition, i. e. with the other
+/// definitions expanded and recursion compiled through `Y`.
+pub fn get(name: &str) -> Option<Exp<String>> {
+    ENVIRONMENT.get(name)?;
+    let mut exp = builder::unbounded_var(name.to_string());
+    ENVIRONMENT.expand(&mut exp);
+    Some(exp)
+}
+
+macro_rules! constants {
+    ($($(#[$meta:meta])* $id:ident = $name:literal;)*) => {
+        $(
+            $(#[$meta])*
+            pub static $id: LazyLock<Exp<String>> =
+                LazyLock::new(|| get($name).expect("defined in the prelude"));
+        )*
+    };
+}
+
+constants! {
+    /// `I = λx. x`
+    I = "I";
+    /// `K = λx. λy. x`
+    K = "K";
+    /// `S = λx. λy. λz. x z (y z)`
+    S = "S";
+    /// `True = λx. λy. x`
+    TRUE = "True";
+    /// `False = λx. λy. y`
+    FALSE = "False";
+    /// `If = λb. λt. λf. b t f`
+    IF = "If";
+    /// `Not = λb. b False True`
+    NOT = "Not";
+    /// `And = λp. λq. p q p`
+    AND = "And";
+    /// `Or = λp. λq. p p q`
+    OR = "Or";
+    /// `Pair = λx. λy. λf. f x y`
+    PAIR = "Pair";
+    /// `Fst = λp. p True`
+    FST = "Fst";
+    /// `Snd = λp. p False`
+    SND = "Snd";
+    /// `Succ = λn. λf. λx. f (n f x)`
+    SUCC = "Succ";
+    /// `Pred`, where `Pred 0 = 0`
+    PRED = "Pred";
+    /// `Plus = λm. λn. λf. λx. m f (n f x)`
+    PLUS = "Plus";
+    /// Truncated subtraction `Sub = λm. λn. n Pred m`
+    SUB = "Sub";
+    /// `Mul = λm. λn. λf. m (n f)`
+    MUL = "Mul";
+    /// `IsZero = λn. n (λx. False) True`
+    IS_ZERO = "IsZero";
+    /// `Y = λf. (λx. f (x x)) (λx. f (x x))`
+    Y = "Y";
+    /// `Z = λf. (λx. f (λv. x x v)) (λx. f (λv. x x v))`
+    Z = "Z";
+    /// `Nil = λnil. λcons. nil`
+    NIL = "Nil";
+    /// `Cons = λhead. λtail. λnil. λcons. cons head tail`
+    CONS = "Cons";
+    /// `Eval`, the self-interpreter of [quoted](Exp::quote) expressions
+    EVAL = "Eval";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{environment, module, parse_options};
+    use crate::{
+        env::Expansion,
+        lambda,
+        numeral::{decode, Encoding},
+        Error,
+    };
+
+    #[test]
+    fn test_documented() {
+        for def in module().definitions {
+            assert!(def.doc.is_some(), "`{}` isn't documented", def.name);
+        }
+    }
+
+    #[test]
+    fn test_semantics() -> Result<(), Error> {
+        let env = environment().with_expansion(Expansion::Lazy);
+        let options = parse_options();
+        let eval = |s: &str| -> Result<_, Error> {
+            let (mut e, _, _) = options.parse_exp_with_spans(s)?;
+            env.simplify(&mut e)?;
+            Ok(e)
+        };
+        let num = |s: &str| -> Result<_, Error> { Ok(decode(&eval(s)?, Encoding::Church)) };
+
+        let (t, f) = (lambda!(x.y.x), lambda!(x.y.y));
+        for (p, q) in [(true, true), (true, false), (false, true), (false, false)] {
+            let name = |b| if b { "True" } else { "False" };
+            let (a, b) = (name(p), name(q));
+            let bool = |b| if b { t.clone() } else { f.clone() };
+            assert_eq!(eval(&format!("And {} {}", a, b))?, bool(p && q));
+            assert_eq!(eval(&format!("Or {} {}", a, b))?, bool(p || q));
+            assert_eq!(eval(&format!("Xor {} {}", a, b))?, bool(p != q));
+            assert_eq!(eval(&format!("Not {}", a))?, bool(!p));
+        }
+        assert_eq!(eval("If True a b")?, lambda!(a));
+        assert_eq!(eval("Snd (Pair a b)")?, lambda!(b));
+        assert_eq!(eval("Compose f g x")?, lambda!(f (g x)));
+        assert_eq!(eval("S K K x")?, lambda!(x));
+
+        assert_eq!(num("Succ 2")?, Some(3));
+        assert_eq!(num("2 + 3 * 2")?, Some(8));
+        assert_eq!(num("2 ^ 3")?, Some(8));
+        assert_eq!(num("Pred 0")?, Some(0));
+        assert_eq!(num("5 - 2")?, Some(3));
+        assert_eq!(num("2 - 5")?, Some(0));
+        assert_eq!(num("Fact 3")?, Some(6));
+        assert_eq!(eval("IsZero 0")?, t);
+        assert_eq!(eval("3 <= 2")?, f);
+        assert_eq!(eval("2 == 2")?, t);
+        assert_eq!(eval("Y (K a)")?, lambda!(a));
+
+        assert_eq!(num("Length (Cons a (Cons b Nil))")?, Some(2));
+        assert_eq!(
+            num("Sum (Append (Cons 1 Nil) (Map Succ (Cons 2 Nil)))")?,
+            Some(4)
+        );
+        assert_eq!(eval("Head (Tail (Cons a (Cons b Nil)))")?, lambda!(b));
+        assert_eq!(eval("IsNil Nil")?, t);
+        assert_eq!(eval("IsNil (Cons a Nil)")?, f);
+        Ok(())
+    }
+}
@@ -0,0 +1,277 @@
+//! Configurable printing of lambda expressions.
+use crate::{
+    numeral::{self, Encoding},
+    parser::{Assoc, Fixity},
+    Exp,
+};
+use std::{collections::HashMap, fmt::Write};
+
+/// Options of [`Exp::display_with`].
+#[derive(Debug, Clone, Default)]
+pub struct PrintOptions {
+    /// Print subexpressions that are numerals of this encoding as literals.
+    ///
+    /// Note that some numerals coincide with other encodings, e. g. Church's
+    /// `0` is `λx. λy. y`, the Church encoding of `false`.
+    pub numerals: Option<Encoding>,
+    /// Print applications of free variables to two arguments infix, if the
+    /// variables are the names of these operators, e. g. `Plus a b` as
+    /// `a + b`. See [`parse_fixities`](crate::parser::parse_fixities).
+    ///
+    /// If several operators stand for the same name, the least of them in
+    /// lexicographic order is printed.
+    pub fixities: HashMap<String, Fixity>,
+}
+
+/// Helper struct for printing expressions with [`PrintOptions`].
+///
+/// Like [`Exp`], use `{:#}` for extra De Bruijn index information.
+pub struct Pretty<'a> {
+    exp: &'a Exp<String>,
+    options: &'a PrintOptions,
+}
+
+impl<'a> Pretty<'a> {
+    fn with(&self, exp: &'a Exp<String>) -> Self {
+        Self {
+            exp,
+            options: self.options,
+        }
+    }
+    fn numeral(&self, exp: &Exp<String>) -> Option<u64> {
+        self.options
+            .numerals
+            .and_then(|encoding| numeral::decode(exp, encoding))
+    }
+    /// Whether the expression is printed without spaces.
+    fn is_atomic(&self, exp: &Exp<String>) -> bool {
+        matches!(exp, Exp::Var(_)) || self.numeral(exp).is_some()
+    }
+    /// Whether the expression is printed as an abstraction.
+    fn is_abs(&self, exp: &Exp<String>) -> bool {
+        matches!(exp, Exp::Abs(_, _)) && self.numeral(exp).is_none()
+    }
+    /// Whether the expression is printed as an application whose last
+    /// argument is an abstraction, which would swallow anything after it.
+    fn ends_in_abs(&self, exp: &Exp<String>) -> bool {
+        match exp {
+            Exp::App(_, b) => self.numeral(exp).is_none() && self.is_abs(b),
+            _ => false,
+        }
+    }
+    /// The operator, its fixity and operands if the expression is printed infix.
+    fn infix<'b>(
+        &self,
+        exp: &'b Exp<String>,
+    ) -> Option<(&str, &Fixity, &'b Exp<String>, &'b Exp<String>)> {
+        if self.numeral(exp).is_some() {
+            return None;
+        }
+        if let Exp::App(l, b) = exp {
+            if let Exp::App(op, a) = &**l {
+                if let Exp::Var(ident) = &**op {
+                    if ident.1 == 0 {
+                        let (op, fixity) = self
+                            .options
+                            .fixities
+                            .iter()
+                            .filter(|(_, fixity)| fixity.name == ident.0)
+                            .min_by_key(|(op, _)| op.as_str())?;
+                        return Some((op, fixity, a, b));
+                    }
+                }
+            }
+        }
+        None
+    }
+    /// Write an operand of an infix operator of the fixity, with parentheses
+    /// if it binds looser than the operator on the side.
+    fn fmt_operand(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        exp: &Exp<String>,
+        fixity: &Fixity,
+        side: Assoc,
+    ) -> std::fmt::Result {
+        let paren = match self.infix(exp) {
+            Some((_, inner, _, _)) => {
+                inner.precedence < fixity.precedence
+                    || inner.precedence == fixity.precedence
+                        && !(inner.assoc == side && fixity.assoc == side)
+            }
+            None => self.is_abs(exp) || side == Assoc::Left && self.ends_in_abs(exp),
+        };
+        let s = if f.alternate() {
+            format!("{:#}", self.with(exp))
+        } else {
+            format!("{}", self.with(exp))
+        };
+        if paren {
+            write!(f, "({})", s)
+        } else {
+            f.write_str(&s)
+        }
+    }
+}
+
+impl std::fmt::Display for Pretty<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(n) = self.numeral(self.exp) {
+            return write!(f, "{}", n);
+        }
+        if let Some((op, fixity, a, b)) = self.infix(self.exp) {
+            self.fmt_operand(f, a, fixity, Assoc::Left)?;
+            write!(f, " {} ", op)?;
+            return self.fmt_operand(f, b, fixity, Assoc::Right);
+        }
+        match self.exp {
+            Exp::Var(ident) => {
+                if f.alternate() {
+                    write!(f, "{}<{}>", ident.0, ident.1)
+                } else {
+                    f.write_str(&ident.0.to_string())
+                }
+            }
+            Exp::Abs(ident, exp) => {
+                write!(f, "λ{}. ", ident.0)?;
+
+                if f.alternate() {
+                    write!(f, "{:#}", self.with(exp))
+                } else {
+                    write!(f, "{}", self.with(exp))
+                }
+            }
+            Exp::App(l, exp) => {
+                // 如果 l 是 lambda 那么要加括号
+                let l_str = if f.alternate() {
+                    format!("{:#}", self.with(l))
+                } else {
+                    format!("{}", self.with(l))
+                };
+                let exp_str = if f.alternate() {
+                    format!("{:#}", self.with(exp))
+                } else {
+                    format!("{}", self.with(exp))
+                };
+                if self.is_atomic(l) {
+                    f.write_str(&l_str)
+                } else {
+                    f.write_char('(')?;
+                    f.write_str(&l_str)?;
+                    f.write_char(')')
+                }?;
+                f.write_char(' ')?;
+                match **exp {
+                    Exp::App(_, _) if !self.is_atomic(exp) => {
+                        f.write_char('(')?;
+                        f.write_str(&exp_str)?;
+                        f.write_char(')')
+                    }
+                    _ => f.write_str(&exp_str),
+                }
+            }
+        }
+    }
+}
+
+impl Exp<String> {
+    /// Format the expression with options.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::{lambda, numeral::Encoding, pretty::PrintOptions};
+    /// let options = PrintOptions {
+    ///     numerals: Some(Encoding::Church),
+    ///     ..Default::default()
+    /// };
+    /// let pair = lambda!(f. f (f. x. f (f x)) (f. x. f x));
+    /// assert_eq!(pair.display_with(&options).to_string(), "λf. (f 2) 1");
+    /// ```
+    pub fn display_with<'a>(&'a self, options: &'a PrintOptions) -> Pretty<'a> {
+        Pretty { exp: self, options }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PrintOptions;
+    use crate::{
+        lambda,
+        numeral::Encoding,
+        parser::{parse_fixities, ParseOptions},
+        Error,
+    };
+
+    #[test]
+    fn test_display_with() {
+        let y_comb = lambda!(f.(x. f (x x)) (x. f (x x)));
+        let options = PrintOptions::default();
+        assert_eq!(
+            y_comb.display_with(&options).to_string(),
+            y_comb.to_string()
+        );
+        assert_eq!(
+            format!("{:#}", y_comb.display_with(&options)),
+            format!("{:#}", y_comb)
+        );
+
+        let options = PrintOptions {
+            numerals: Some(Encoding::Scott),
+            ..Default::default()
+        };
+        let e = lambda!(g. (g (z. s. s (z. s. z))) (z. s. z) (g (z. s. s)));
+        assert_eq!(
+            e.display_with(&options).to_string(),
+            "λg. ((g 1) 0) (g λz. λs. s)"
+        );
+    }
+
+    #[test]
+    fn test_display_infix() -> Result<(), Error> {
+        let file = r"
+            infixl 6 + = Plus
+            infixl 6 - = Minus
+            infixl 7 * = Mul
+            infixr 8 ^ = Exp
+            infix 4 == = Eq
+        ";
+        let parse_options = ParseOptions {
+            fixities: parse_fixities(file)?,
+            ..Default::default()
+        };
+        let options = PrintOptions {
+            numerals: Some(Encoding::Church),
+            fixities: parse_options.fixities.clone(),
+        };
+        for s in [
+            "a * b + c",
+            "a * (b + c)",
+            "a - (b - c)",
+            "a - b - c",
+            "a ^ b ^ c",
+            "(a ^ b) ^ c",
+            "f a * g 2 == 0",
+            r"(\x. x) + y",
+            r"\x. x + y",
+            r"(f \x. x) + y",
+            "(f (a + b)) c",
+        ] {
+            let (exp, _, _) = parse_options.parse_exp_with_spans(s)?;
+            assert_eq!(exp.display_with(&options).to_string(), s.replace('\\', "λ"));
+        }
+
+        // aliases are printed as the least operator, whatever the order of
+        // the map is
+        let file = "infixl 6 ++ = Plus\ninfixl 6 + = Plus\ninfixl 6 <+> = Plus";
+        for _ in 0..8 {
+            let options = PrintOptions {
+                fixities: parse_fixities(file)?,
+                ..Default::default()
+            };
+            let exp = lambda!(Plus a b);
+            assert_eq!(exp.display_with(&options).to_string(), "a + b");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,139 @@
+//! Mogensen–Scott encoding of expressions as expressions.
+use crate::{Exp, Ident};
+
+/// Node of a quoted expression, `λvar. λapp. λabs. body`.
+fn node(body: Exp<String>) -> Exp<String> {
+    Exp::abs("var", Exp::abs("app", Exp::abs("abs", body)))
+}
+
+/// Quote `exp` under `depth` abstractions, where `binders` are the depths of
+/// the quoted abstractions.
+fn quote(exp: &Exp<String>, depth: u32, binders: &mut Vec<u32>) -> Exp<String> {
+    let depth = depth + 3;
+    let body = match exp {
+        Exp::Var(Ident(name, code)) => {
+            let code = match *code {
+                0 => 0,
+                code => depth - binders[binders.len() - code as usize] + 1,
+            };
+            let var = Exp::Var(Ident(String::from("var"), 3));
+            Exp::app(var, Exp::Var(Ident(name.clone(), code)))
+        }
+        Exp::Abs(Ident(name, _), body) => {
+            binders.push(depth + 1);
+            let body = quote(body, depth + 1, binders);
+            binders.pop();
+            let abs_ = Exp::Var(Ident(String::from("abs"), 1));
+            Exp::app(abs_, Exp::abs(name, body))
+        }
+        Exp::App(func, body) => {
+            let app_ = Exp::Var(Ident(String::from("app"), 2));
+            Exp::app(
+                Exp::app(app_, quote(func, depth, binders)),
+                quote(body, depth, binders),
+            )
+        }
+    };
+    node(body)
+}
+
+/// Inverse of [`quote`].
+fn unquote(exp: &Exp<String>, depth: u32, binders: &mut Vec<u32>) -> Option<Exp<String>> {
+    let depth = depth + 3;
+    let body = exp.into_abs()?.1.into_abs()?.1.into_abs()?.1;
+    let (func, arg) = body.into_app()?;
+    match (func, arg) {
+        (Exp::Var(Ident(_, 3)), Exp::Var(Ident(name, code))) => {
+            let code = match *code {
+                0 => 0,
+                code => {
+                    let level = depth.checked_sub(code)? + 1;
+                    let pos = binders.iter().position(|b| *b == level)?;
+                    (binders.len() - pos) as u32
+                }
+            };
+            Some(Exp::Var(Ident(name.clone(), code)))
+        }
+        (Exp::Var(Ident(_, 1)), Exp::Abs(Ident(name, _), body)) => {
+            binders.push(depth + 1);
+            let body = unquote(body, depth + 1, binders);
+            binders.pop();
+            Some(Exp::abs(name, body?))
+        }
+        (Exp::App(app_, func), body) if app_.into_ident()?.1 == 2 => Some(Exp::app(
+            unquote(func, depth, binders)?,
+            unquote(body, depth, binders)?,
+        )),
+        _ => None,
+    }
+}
+
+impl Exp<String> {
+    /// Encode the syntax of `self` as an expression, where
+    ///
+    /// - `⌜x⌝ = λvar. λapp. λabs. var x`,
+    /// - `⌜M N⌝ = λvar. λapp. λabs. app ⌜M⌝ ⌜N⌝`,
+    /// - `⌜λx. M⌝ = λvar. λapp. λabs. abs (λx. ⌜M⌝)`.
+    ///
+    /// The self-interpreter `Eval` of the [prelude](crate::prelude) reduces
+    /// `Eval ⌜M⌝` to `M`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::{lambda, prelude};
+    /// let m = lambda!(x. f x);
+    /// assert_eq!(m.quote().unquote(), Some(m.clone()));
+    ///
+    /// let mut e = lambda!({prelude::EVAL} {m.quote()});
+    /// e.simplify(false).unwrap();
+    /// assert_eq!(e.purify(), m.purify());
+    /// ```
+    pub fn quote(&self) -> Exp<String> {
+        quote(self, 0, &mut vec![])
+    }
+    /// Decode an expression quoted by [`Exp::quote`], return `None` if it
+    /// isn't one.
+    pub fn unquote(&self) -> Option<Exp<String>> {
+        unquote(self, 0, &mut vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{lambda, numeral, prelude, Error};
+
+    #[test]
+    fn test_quote() {
+        let exps = [
+            lambda!(x),
+            lambda!(x. y. z. x z (y z)),
+            lambda!(x. f (y. x y) x),
+            prelude::get("Pred").unwrap(),
+            prelude::get("Y").unwrap(),
+        ];
+        for m in exps {
+            let q = m.quote();
+            assert_eq!(q.unquote(), Some(m.clone()), "{}", m);
+
+            // quoted expressions are normal forms
+            let mut e = q.clone();
+            assert!(!e.eval_normal_order(false, false));
+        }
+        assert_eq!(lambda!(x.x).unquote(), None);
+        assert_eq!(lambda!(v. a. b. v a).unquote(), None);
+    }
+
+    #[test]
+    fn test_eval() -> Result<(), Error> {
+        let m = lambda!(x. y. (z. z) x);
+        let mut e = lambda!({prelude::EVAL} {m.quote()});
+        e.simplify(false)?;
+        assert_eq!(e.purify(), lambda!(x.y.x).purify());
+
+        let mut e = lambda!({prelude::EVAL} {lambda!({prelude::PLUS} 2 3).quote()});
+        e.simplify(false)?;
+        assert_eq!(numeral::decode(&e, numeral::Encoding::Church), Some(5));
+        Ok(())
+    }
+}
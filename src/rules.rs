@@ -0,0 +1,282 @@
+//! User-defined rewrite rules, also known as delta rules.
+//!
+//! A [`Rule`] rewrites subterms matching its left-hand side [`Pattern`] to
+//! an expression built from the parts bound to the pattern variables.
+//! [`Rules`] are applied during normal order reduction as the [`Rewrite`] of
+//! [`EvalOptions`](crate::EvalOptions), before the beta-reduction of every
+//! subterm, so that domain-specific encodings can be accelerated along with
+//! the built-in Church arithmetic.
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{lambda, numeral::{decode, encode, Encoding}, rules::{Pattern, Rule, Rules}, EvalOptions};
+//! let double = Rule::new(Pattern::new(lambda!(Double n), ["n"]), |bindings| {
+//!     let n = decode(&bindings["n"], Encoding::Church)?;
+//!     Some(encode(2 * n, Encoding::Church))
+//! });
+//! let swap = Rule::template(Pattern::new(lambda!(Swap (Pair a b)), ["a", "b"]), lambda!(Pair b a));
+//! let rules = Rules::from_iter([double, swap]);
+//!
+//! let mut e = lambda!(Swap (Pair (Double 21) x));
+//! let options = EvalOptions {
+//!     rewrite: Some(&rules),
+//!     ..Default::default()
+//! };
+//! e.simplify_with(&options).unwrap();
+//! assert_eq!(e, lambda!(Pair x {encode(42, Encoding::Church)}));
+//! ```
+use crate::{decode::unshift, Exp, Ident, Rewrite};
+use std::collections::HashMap;
+
+/// Parts of an expression bound to the pattern variables by
+/// [`Pattern::matches`].
+pub type Bindings = HashMap<String, Exp<String>>;
+
+/// Whether `a` and `b` are equal up to the names of bound variables.
+fn alpha_eq(a: &Exp<String>, b: &Exp<String>) -> bool {
+    match (a, b) {
+        (Exp::Var(Ident(a, 0)), Exp::Var(Ident(b, 0))) => a == b,
+        (Exp::Var(Ident(_, a)), Exp::Var(Ident(_, b))) => a == b,
+        (Exp::Abs(_, a), Exp::Abs(_, b)) => alpha_eq(a, b),
+        (Exp::App(a, a_body), Exp::App(b, b_body)) => alpha_eq(a, b) && alpha_eq(a_body, b_body),
+        _ => false,
+    }
+}
+
+/// Left-hand side of a rule.
+///
+/// Free variables of the expression named in `vars` are pattern variables,
+/// which match any subterm not referring to the abstractions of the pattern,
+/// the same one if repeated. Other free variables match themselves, and
+/// abstractions match up to alpha-equivalence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pattern {
+    exp: Exp<String>,
+    vars: Vec<String>,
+}
+
+impl Pattern {
+    /// Pattern of `exp` with the pattern variables `vars`.
+    pub fn new<S: Into<String>>(exp: Exp<String>, vars: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            exp,
+            vars: vars.into_iter().map(Into::into).collect(),
+        }
+    }
+    /// Match `exp`, return the bindings of the pattern variables.
+    ///
+    /// Bound parts are moved out of the abstractions of the pattern, i. e.
+    /// they are valid where `exp` is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use lamcalc::{lambda, rules::Pattern};
+    /// let pattern = Pattern::new(lambda!(x. f a x), ["a"]);
+    /// assert_eq!(pattern.matches(&lambda!(y. f (z. z) y)).unwrap()["a"], lambda!(z. z));
+    /// // `a` can't refer to `y`
+    /// assert_eq!(pattern.matches(&lambda!(y. f y y)), None);
+    /// ```
+    pub fn matches(&self, exp: &Exp<String>) -> Option<Bindings> {
+        let mut bindings = Bindings::new();
+        self.match_with_depth(&self.exp, exp, 0, &mut bindings)?;
+        Some(bindings)
+    }
+    fn match_with_depth(
+        &self,
+        pattern: &Exp<String>,
+        exp: &Exp<String>,
+        depth: u32,
+        bindings: &mut Bindings,
+    ) -> Option<()> {
+        match (pattern, exp) {
+            (Exp::Var(Ident(name, 0)), _) if self.vars.contains(name) => {
+                let exp = unshift(exp, depth, 0)?;
+                match bindings.get(name) {
+                    Some(bound) if !alpha_eq(bound, &exp) => None,
+                    Some(_) => Some(()),
+                    None => {
+                        bindings.insert(name.clone(), exp);
+                        Some(())
+                    }
+                }
+            }
+            (Exp::Var(Ident(name, 0)), Exp::Var(Ident(other, 0))) => (name == other).then_some(()),
+            (Exp::Var(Ident(_, code)), Exp::Var(Ident(_, other))) => {
+                (*code != 0 && code == other).then_some(())
+            }
+            (Exp::Abs(_, pattern), Exp::Abs(_, body)) => {
+                self.match_with_depth(pattern, body, depth + 1, bindings)
+            }
+            (Exp::App(pattern_func, pattern_body), Exp::App(func, body)) => {
+                self.match_with_depth(pattern_func, func, depth, bindings)?;
+                self.match_with_depth(pattern_body, body, depth, bindings)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Builder of the right-hand side of a rule, which may reject the bindings
+/// by returning `None`.
+pub type Builder = Box<dyn Fn(&Bindings) -> Option<Exp<String>> + Send + Sync>;
+
+/// A rewrite rule `lhs → rhs`.
+pub struct Rule {
+    /// Pattern of the rewritten subterms
+    pub lhs: Pattern,
+    /// Builder of the result from the bindings of the pattern variables
+    pub rhs: Builder,
+}
+
+impl std::fmt::Debug for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Rule").field("lhs", &self.lhs).finish()
+    }
+}
+
+impl Rule {
+    /// Rule building its result by `rhs`.
+    pub fn new(
+        lhs: Pattern,
+        rhs: impl Fn(&Bindings) -> Option<Exp<String>> + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            lhs,
+            rhs: Box::new(rhs),
+        }
+    }
+    /// Rule rewriting to `rhs` with the pattern variables substituted, e. g.
+    /// `Fst (Pair a b) → a`.
+    pub fn template(lhs: Pattern, rhs: Exp<String>) -> Self {
+        Self::new(lhs, move |bindings| {
+            let mut exp = rhs.clone();
+            exp.for_each_var(|v, dep| {
+                if let Exp::Var(Ident(name, 0)) = &v {
+                    if let Some(bound) = bindings.get(name) {
+                        let mut bound = bound.clone();
+                        bound.shift_outer_captured_var(dep as isize);
+                        *v = bound;
+                    }
+                }
+            });
+            Some(exp)
+        })
+    }
+    /// Rewrite `exp` if it matches, return `false` if nothing changed.
+    pub fn apply(&self, exp: &mut Exp<String>) -> bool {
+        let Some(res) = self
+            .lhs
+            .matches(exp)
+            .and_then(|bindings| (self.rhs)(&bindings))
+        else {
+            return false;
+        };
+        *exp = res;
+        true
+    }
+}
+
+/// Rewrite rules applied in order, the first applicable one wins.
+#[derive(Debug, Default)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl FromIterator<Rule> for Rules {
+    fn from_iter<I: IntoIterator<Item = Rule>>(iter: I) -> Self {
+        Self {
+            rules: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Rules {
+    /// Add a rule after the others.
+    pub fn push(&mut self, rule: Rule) {
+        self.rules.push(rule);
+    }
+    /// Rewrite `exp` by the first applicable rule, return `false` if nothing
+    /// changed.
+    pub fn apply(&self, exp: &mut Exp<String>) -> bool {
+        self.rules.iter().any(|rule| rule.apply(exp))
+    }
+}
+
+impl Rewrite<String> for Rules {
+    fn rewrite(&self, exp: &mut Exp<String>) -> bool {
+        self.apply(exp)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Pattern, Rule, Rules};
+    use crate::{lambda, numeral, prelude, Error, EvalOptions};
+
+    #[test]
+    fn test_template() -> Result<(), Error> {
+        let rules = Rules::from_iter([
+            Rule::template(
+                Pattern::new(lambda!(Fst (Pair a b)), ["a", "b"]),
+                lambda!(a),
+            ),
+            Rule::template(Pattern::new(lambda!(K a), ["a"]), lambda!(y.a)),
+            Rule::template(Pattern::new(lambda!(Same a a), ["a"]), lambda!(x.y.x)),
+        ]);
+        let options = EvalOptions {
+            rewrite: Some(&rules),
+            ..Default::default()
+        };
+        let mut e = lambda!(z. Fst (Pair z w));
+        e.simplify_with(&options)?;
+        assert_eq!(e, lambda!(z.z));
+
+        // bindings are shifted under the abstractions of the result
+        let mut e = lambda!(z. K z);
+        e.simplify_with(&options)?;
+        assert_eq!(e, lambda!(z.y.z));
+
+        let mut e = lambda!(Same(x.x)(y.y));
+        e.simplify_with(&options)?;
+        assert_eq!(e, lambda!(x.y.x));
+        let mut e = lambda!(Same a b);
+        assert!(!e.eval_normal_order_with(false, &options));
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder() -> Result<(), Error> {
+        let church = numeral::Encoding::Church;
+        let mut rules = Rules::default();
+        rules.push(Rule::new(
+            Pattern::new(lambda!(Double n), ["n"]),
+            move |b| {
+                let n = numeral::decode(&b["n"], church)?;
+                Some(numeral::encode(2 * n, church))
+            },
+        ));
+
+        let mut options = EvalOptions {
+            rewrite: Some(&rules),
+            ..Default::default()
+        };
+        // rules apply before beta-reduction, and the arguments of a rejected
+        // match are reduced as usual
+        let mut e = lambda!(Double ((x. x) 3));
+        assert!(e.eval_normal_order_with(false, &options));
+        assert_eq!(e, lambda!(Double 3));
+        assert!(e.eval_normal_order_with(false, &options));
+        assert_eq!(numeral::decode(&e, church), Some(6));
+
+        // and along with the accelerator
+        options.optimize = true;
+        let mul = prelude::get("Mul").unwrap();
+        let mut e = lambda!(Double ({mul} 2 ((x. x) 3)));
+        let stats = e.simplify_with(&options)?;
+        assert_eq!(numeral::decode(&e, church), Some(12));
+        assert_eq!((stats.steps, stats.accelerations), (3, 1));
+        Ok(())
+    }
+}
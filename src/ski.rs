@@ -0,0 +1,338 @@
+//! Combinatory logic, and bracket abstraction from lambda expressions.
+//!
+//! A [`Combinator`] term is built from the combinators
+//!
+//! | combinator | rule             |
+//! |------------|------------------|
+//! | `I`        | `I x = x`        |
+//! | `K`        | `K x y = x`      |
+//! | `S`        | `S x y z = x z (y z)` |
+//! | `B`        | `B x y z = x (y z)`   |
+//! | `C`        | `C x y z = x z y`     |
+//! | `W`        | `W x y = x y y`       |
+//!
+//! and free variables, by application only. Bracket abstraction eliminates
+//! the abstractions of a lambda expression, see [`Algorithm`].
+//!
+//! # Example
+//!
+//! ```
+//! # use lamcalc::{lambda, ski::{Algorithm, Combinator}};
+//! let flip = lambda!(f x y. f y x);
+//! let naive = Combinator::from_exp(&flip, Algorithm::Naive);
+//! let turner = Combinator::from_exp(&flip, Algorithm::Turner);
+//! assert_eq!(turner.to_string(), "C");
+//! assert!(naive.size() > flip.size() && flip.size() > turner.size());
+//!
+//! let mut e = Combinator::from_exp(&lambda!({flip} a b c), Algorithm::Turner);
+//! e.simplify().unwrap();
+//! assert_eq!(e.to_exp(), lambda!(a c b));
+//! ```
+use crate::{builder, lambda, Error, Exp, Ident, SIMPLIFY_LIMIT};
+
+/// Term of combinatory logic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Combinator<T: Clone + Eq> {
+    /// `S = λx. λy. λz. x z (y z)`
+    S,
+    /// `K = λx. λy. x`
+    K,
+    /// `I = λx. x`
+    I,
+    /// `B = λx. λy. λz. x (y z)`
+    B,
+    /// `C = λx. λy. λz. x z y`
+    C,
+    /// `W = λx. λy. x y y`
+    W,
+    /// Free variable
+    Var(T),
+    /// Application
+    App(Box<Combinator<T>>, Box<Combinator<T>>),
+}
+
+/// Bracket abstraction algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// `[x] x = I`, `[x] M = K M` for variables and combinators `M`, and
+    /// `[x] (M N) = S ([x] M) ([x] N)`, producing S, K and I only
+    Naive,
+    /// Turner's algorithm, which abstracts `x` from the parts containing it
+    /// only, by B, C, W and eta-reduction, producing much smaller terms
+    #[default]
+    Turner,
+}
+
+/// Variable of a term under translation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Slot<T: Clone + Eq> {
+    Free(T),
+    /// De Bruijn index of a variable to be abstracted
+    Bound(u32),
+}
+
+type Term<T> = Combinator<Slot<T>>;
+
+fn app<T: Clone + Eq>(func: Combinator<T>, body: Combinator<T>) -> Combinator<T> {
+    Combinator::App(Box::new(func), Box::new(body))
+}
+
+/// Whether the variable of index 1 occurs.
+fn occurs<T: Clone + Eq>(term: &Term<T>) -> bool {
+    match term {
+        Combinator::Var(Slot::Bound(1)) => true,
+        Combinator::App(func, body) => occurs(func) || occurs(body),
+        _ => false,
+    }
+}
+
+/// Move a term not containing the variable of index 1 out of its
+/// abstraction.
+fn lower<T: Clone + Eq>(term: Term<T>) -> Term<T> {
+    match term {
+        Combinator::Var(Slot::Bound(code)) => Combinator::Var(Slot::Bound(code - 1)),
+        Combinator::App(func, body) => app(lower(*func), lower(*body)),
+        term => term,
+    }
+}
+
+/// Abstract the variable of index 1.
+fn abstract_var<T: Clone + Eq>(term: Term<T>, algorithm: Algorithm) -> Term<T> {
+    use Combinator::*;
+    match algorithm {
+        Algorithm::Naive => match term {
+            Var(Slot::Bound(1)) => I,
+            App(func, body) => app(
+                app(S, abstract_var(*func, algorithm)),
+                abstract_var(*body, algorithm),
+            ),
+            term => app(K, lower(term)),
+        },
+        Algorithm::Turner => {
+            if !occurs(&term) {
+                return app(K, lower(term));
+            }
+            let (func, body) = match term {
+                App(func, body) => (*func, *body),
+                _ => return I,
+            };
+            let is_var = matches!(body, Var(Slot::Bound(1)));
+            match (occurs(&func), is_var) {
+                (false, true) => lower(func),
+                (false, false) => app(app(B, lower(func)), abstract_var(body, algorithm)),
+                (true, true) => app(W, abstract_var(func, algorithm)),
+                (true, false) if !occurs(&body) => {
+                    app(app(C, abstract_var(func, algorithm)), lower(body))
+                }
+                (true, false) => app(
+                    app(S, abstract_var(func, algorithm)),
+                    abstract_var(body, algorithm),
+                ),
+            }
+        }
+    }
+}
+
+fn translate<T: Clone + Eq>(exp: &Exp<T>, algorithm: Algorithm) -> Term<T> {
+    match exp {
+        Exp::Var(Ident(name, 0)) => Combinator::Var(Slot::Free(name.clone())),
+        Exp::Var(Ident(_, code)) => Combinator::Var(Slot::Bound(*code)),
+        Exp::Abs(_, body) => abstract_var(translate(body, algorithm), algorithm),
+        Exp::App(func, body) => app(translate(func, algorithm), translate(body, algorithm)),
+    }
+}
+
+impl<T: Clone + Eq> Combinator<T> {
+    /// Translate a lambda expression by bracket abstraction, where free
+    /// variables stay variables.
+    pub fn from_exp(exp: &Exp<T>, algorithm: Algorithm) -> Self {
+        fn free<T: Clone + Eq>(term: Term<T>) -> Combinator<T> {
+            use Combinator::*;
+            match term {
+                S => S,
+                K => K,
+                I => I,
+                B => B,
+                C => C,
+                W => W,
+                Var(Slot::Free(name)) => Var(name),
+                Var(Slot::Bound(_)) => panic!("index exceeds the enclosing abstractions"),
+                App(func, body) => app(free(*func), free(*body)),
+            }
+        }
+        free(translate(exp, algorithm))
+    }
+    /// Number of combinators and variables.
+    pub fn size(&self) -> usize {
+        match self {
+            Combinator::App(func, body) => func.size() + body.size(),
+            _ => 1,
+        }
+    }
+    /// Number of arguments the combinator takes.
+    fn arity(&self) -> Option<usize> {
+        match self {
+            Combinator::I => Some(1),
+            Combinator::K | Combinator::W => Some(2),
+            Combinator::S | Combinator::B | Combinator::C => Some(3),
+            _ => None,
+        }
+    }
+    /// Contract a combinator applied to exactly its arguments.
+    fn contract(self) -> Self {
+        use Combinator::*;
+        let mut args = vec![];
+        let mut head = self;
+        while let App(func, arg) = head {
+            args.push(*arg);
+            head = *func;
+        }
+        // arguments in reverse order
+        let mut arg = || args.pop().expect("applied to its arguments");
+        match head {
+            I => arg(),
+            K => arg(),
+            S => {
+                let (x, y, z) = (arg(), arg(), arg());
+                app(app(x, z.clone()), app(y, z))
+            }
+            B => {
+                let (x, y, z) = (arg(), arg(), arg());
+                app(x, app(y, z))
+            }
+            C => {
+                let (x, y, z) = (arg(), arg(), arg());
+                app(app(x, z), y)
+            }
+            W => {
+                let (x, y) = (arg(), arg());
+                app(app(x, y.clone()), y)
+            }
+            _ => unreachable!("contracting a variable"),
+        }
+    }
+    /// Make one step of weak reduction, where the leftmost, outermost
+    /// combinator applied to its arguments is contracted first, return
+    /// `false` if nothing changes.
+    pub fn reduce(&mut self) -> bool {
+        let mut args = vec![];
+        let mut head: &mut Combinator<T> = self;
+        while let Combinator::App(func, arg) = head {
+            args.push(arg.as_mut());
+            head = func;
+        }
+        let len = args.len();
+        match head.arity() {
+            Some(arity) if arity <= len => {
+                // the redex is the application to the first `arity` arguments
+                let mut redex: &mut Combinator<T> = self;
+                for _ in arity..len {
+                    let Combinator::App(func, _) = redex else {
+                        unreachable!()
+                    };
+                    redex = func;
+                }
+                let term = std::mem::replace(redex, Combinator::I);
+                *redex = term.contract();
+                true
+            }
+            _ => args.into_iter().rev().any(|arg| arg.reduce()),
+        }
+    }
+    /// Weakly reduce repeatedly for at most [`SIMPLIFY_LIMIT`] times.
+    pub fn simplify(&mut self) -> Result<&mut Self, Error> {
+        for _ in 0..SIMPLIFY_LIMIT {
+            if !self.reduce() {
+                return Ok(self);
+            }
+        }
+        Err(Error::SimplifyLimitExceeded)
+    }
+}
+
+impl Combinator<String> {
+    /// Translate back to a lambda expression, substituting the definitions
+    /// of the combinators.
+    pub fn to_exp(&self) -> Exp<String> {
+        match self {
+            Combinator::S => lambda!(x. y. z. x z (y z)),
+            Combinator::K => lambda!(x.y.x),
+            Combinator::I => lambda!(x.x),
+            Combinator::B => lambda!(x. y. z. x (y z)),
+            Combinator::C => lambda!(x. y. z. x z y),
+            Combinator::W => lambda!(x. y. x y y),
+            Combinator::Var(name) => builder::unbounded_var(name.clone()),
+            Combinator::App(func, body) => builder::app(vec![func.to_exp(), body.to_exp()]),
+        }
+    }
+}
+
+impl<T: Clone + Eq + std::fmt::Display> std::fmt::Display for Combinator<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Combinator::S => f.write_str("S"),
+            Combinator::K => f.write_str("K"),
+            Combinator::I => f.write_str("I"),
+            Combinator::B => f.write_str("B"),
+            Combinator::C => f.write_str("C"),
+            Combinator::W => f.write_str("W"),
+            Combinator::Var(name) => name.fmt(f),
+            Combinator::App(func, body) => match body.as_ref() {
+                Combinator::App(..) => write!(f, "{} ({})", func, body),
+                _ => write!(f, "{} {}", func, body),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Algorithm, Combinator};
+    use crate::{lambda, prelude, Error, Exp};
+
+    /// Beta-eta normal form, up to the names of bound variables.
+    fn normalize(mut exp: Exp<String>) -> Exp<()> {
+        while exp.eval_normal_order(true, false) {}
+        exp.purify()
+    }
+
+    #[test]
+    fn test_bracket_abstraction() {
+        let k = lambda!(x.y.x);
+        assert_eq!(
+            Combinator::from_exp(&k, Algorithm::Naive).to_string(),
+            "S (K K) I"
+        );
+        assert_eq!(Combinator::from_exp(&k, Algorithm::Turner).to_string(), "K");
+        let e = lambda!(x. y. y x x);
+        assert_eq!(
+            Combinator::from_exp(&e, Algorithm::Turner).to_string(),
+            "W (B C (C I))"
+        );
+
+        for name in ["S", "Pair", "Plus", "Pred", "Pow"] {
+            let exp = prelude::get(name).unwrap();
+            for algorithm in [Algorithm::Naive, Algorithm::Turner] {
+                let c = Combinator::from_exp(&exp, algorithm);
+                assert_eq!(normalize(c.to_exp()), normalize(exp.clone()), "{}", name);
+            }
+        }
+    }
+
+    #[test]
+    fn test_reduce() -> Result<(), Error> {
+        let plus = prelude::get("Plus").unwrap();
+        for algorithm in [Algorithm::Naive, Algorithm::Turner] {
+            let mut c = Combinator::from_exp(&lambda!({plus} 2 3 f x), algorithm);
+            c.simplify()?;
+            assert_eq!(c.to_exp(), lambda!(f (f (f (f (f x))))));
+        }
+
+        // no reduction under missing arguments
+        let mut c = Combinator::from_exp(&lambda!(f. (x. x) f), Algorithm::Naive);
+        let before = c.clone();
+        c.simplify()?;
+        assert_eq!(c, before);
+        Ok(())
+    }
+}
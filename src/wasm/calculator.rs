@@ -1,6 +1,10 @@
-use std::collections::HashMap;
-
-use crate::{parser, wasm::exp::JsExp, Error, Exp};
+use crate::{
+    loader::{Loader, MemoryResolver},
+    module::Definition,
+    parser, prelude,
+    wasm::exp::JsExp,
+    Error, Exp,
+};
 use serde::Serialize;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 // use web_sys::console;
@@ -43,6 +47,7 @@ impl Step {
             redex: id,
             alpha: alpha_id,
         });
+        raw_exp.rename_for_display();
         let display_exp = JsExp::from_exp(&raw_exp);
         let id = raw_exp.to_string();
         Ok(Self {
@@ -59,6 +64,7 @@ impl Step {
             redex: id,
             alpha: alpha_id,
         });
+        raw_exp.rename_for_display();
         let display_exp = JsExp::from_exp(&raw_exp);
         let id = raw_exp.to_string();
         Ok(Self {
@@ -88,6 +94,7 @@ impl Step {
         assert!(var.into_ident().unwrap().0 == name);
         *var = exp.to_owned();
         self.last_action = Some(Mutation::SubstAlpha { alpha: id, name });
+        raw_exp.rename_for_display();
         let display_exp = JsExp::from_exp(&raw_exp);
         let id = raw_exp.to_string();
         Ok(Self {
@@ -103,23 +110,50 @@ impl Step {
 #[wasm_bindgen]
 pub struct Calculator {
     steps: Vec<Step>,
-    defs: HashMap<String, Exp<String>>,
+    /// definitions in the order of adding, without shadowed ones
+    defs: Vec<Definition>,
+    /// files that definitions can import
+    files: MemoryResolver,
+}
+
+/// Serialize definitions as a map from names to expressions, keeping the order.
+struct OrderedDefs<'a>(&'a [Definition]);
+
+impl Serialize for OrderedDefs<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(
+            self.0
+                .iter()
+                .map(|def| (&def.name, JsExp::from_exp(&def.exp))),
+        )
+    }
 }
 
 /// A calculator for lambda expression, supporting evaluation and simplification step by step.
 #[wasm_bindgen]
 impl Calculator {
-    /// New instance
+    /// New instance, where definitions can `import "prelude.lambda"`
     #[wasm_bindgen(constructor)]
     pub fn new() -> Self {
+        let mut files = MemoryResolver::new();
+        files.insert(prelude::FILE_NAME, prelude::SOURCE);
         Self {
             steps: Vec::new(),
-            defs: HashMap::new(),
+            defs: Vec::new(),
+            files,
+        }
+    }
+    /// New instance with the definitions of the prelude
+    pub fn with_prelude() -> Self {
+        Self {
+            defs: prelude::module().visible().cloned().collect(),
+            ..Self::new()
         }
     }
     /// Set initial expression
     pub fn init(&mut self, expr: &str) -> Result<(), String> {
-        let (raw_exp, _) = parser::parse_exp(expr).map_err(|e| e.to_string())?;
+        let (mut raw_exp, _) = parser::parse_exp(expr).map_err(|e| e.to_string())?;
+        raw_exp.rename_for_display();
         let wasm_exp = JsExp::from_exp(&raw_exp);
         let id = raw_exp.to_string();
         self.steps = vec![Step {
@@ -170,10 +204,12 @@ impl Calculator {
         alpha_id: u32,
     ) -> Result<(), String> {
         let mut last = self.trim_steps(step)?;
-        let exp = self
+        let exp = &self
             .defs
-            .get(name)
-            .ok_or(format!("expression not found name = {}", name))?;
+            .iter()
+            .find(|def| def.name == name)
+            .ok_or(format!("expression not found name = {}", name))?
+            .exp;
         let cur = last
             .replace_by_alpha_id(alpha_id, name.to_string(), exp)
             .map_err(|e| format!("化简错误：{}", e))?;
@@ -181,29 +217,37 @@ impl Calculator {
         self.steps.push(cur);
         Ok(())
     }
+    /// add a file that definitions can import by `import "name"`
+    pub fn add_file(&mut self, name: &str, content: &str) {
+        self.files.insert(name, content);
+    }
     /// add definitions from multiline content
     pub fn add_defs(&mut self, content: &str) -> Result<(), String> {
-        let (map, _) = parser::parse_file(content).map_err(|e| e.to_string())?;
-        for (name, exp) in map {
-            self.defs.insert(name, exp);
+        let module = Loader::new(self.files.clone())
+            .load_content("", content)
+            .map_err(|e| e.to_string())?;
+        for def in module.visible() {
+            self.defs.retain(|d| d.name != def.name);
+            self.defs.push(def.clone());
         }
         Ok(())
     }
+    /// Get the doc comment of a named definition
+    pub fn get_doc(&self, name: &str) -> Option<String> {
+        let def = self.defs.iter().find(|def| def.name == name)?;
+        def.doc.clone()
+    }
     /// Get all steps
     ///
     /// return `Step`
     pub fn history(&self) -> Result<JsValue, String> {
         serde_wasm_bindgen::to_value(&self.steps).map_err(|e| e.to_string())
     }
-    /// Get all named definitions
+    /// Get all named definitions in the order of adding
     ///
-    /// return `HashMap<String, JsExp>`
+    /// return `Map<String, JsExp>`
     pub fn get_defs(&self) -> Result<JsValue, String> {
-        let mut jsmap = HashMap::new();
-        for (name, exp) in &self.defs {
-            jsmap.insert(name, JsExp::from_exp(exp));
-        }
-        serde_wasm_bindgen::to_value(&jsmap).map_err(|e| e.to_string())
+        serde_wasm_bindgen::to_value(&OrderedDefs(&self.defs)).map_err(|e| e.to_string())
     }
 }
 
@@ -216,6 +260,18 @@ impl Default for Calculator {
 #[cfg(test)]
 mod tests {
     use super::Calculator;
+    use crate::{
+        lambda,
+        wasm::exp::{InnerExp, JsExp},
+    };
+
+    fn redex_id(exp: &JsExp) -> u32 {
+        match &exp.inner {
+            InnerExp::App(app) => app.beta_redex.unwrap(),
+            InnerExp::Abs(abs) => redex_id(&abs.body),
+            InnerExp::Var(_) => panic!("no redex"),
+        }
+    }
 
     #[test]
     fn test_calculator() -> Result<(), String> {
@@ -223,11 +279,43 @@ mod tests {
         calc.init("I y")?;
         calc.add_defs(
             r"
+            // identity
             I = \x. x
             K = \x. \y. x
         ",
         )?;
+        calc.add_file("lib/i.lambda", r"I = \y. y");
+        calc.add_defs(r#"import "lib/i.lambda" as Lib"#)?;
+        calc.add_defs(r"I = \y. y")?;
+        let names: Vec<_> = calc.defs.iter().map(|def| def.name.as_str()).collect();
+        assert_eq!(names, ["K", "Lib.I", "I"]);
+        assert_eq!(calc.get_doc("I"), None);
+        calc.replace_def_alpha(0, "I", 1)?;
+        calc.beta_reduce(1, redex_id(&calc.steps[1].display_exp))?;
+        assert_eq!(calc.steps[2].raw_exp.to_string(), "y");
+
+        // `K x` would show `λy. λx. x` without renaming
+        calc.init(r"\x. K x")?;
+        calc.replace_def_alpha(0, "K", 1)?;
+        calc.beta_reduce(1, redex_id(&calc.steps[1].display_exp))?;
+        assert_eq!(format!("{:#}", calc.steps[2].raw_exp), "λx. λy. x<2>");
+        calc.init(r"\y. K y")?;
+        calc.replace_def_alpha(0, "K", 1)?;
+        calc.beta_reduce(1, redex_id(&calc.steps[1].display_exp))?;
+        assert_eq!(format!("{:#}", calc.steps[2].raw_exp), "λy. λy1. y<2>");
 
         Ok(())
     }
+
+    #[test]
+    fn test_prelude() -> Result<(), String> {
+        let mut calc = Calculator::with_prelude();
+        assert!(calc.get_doc("Succ").is_some());
+        calc.add_defs(r#"import "prelude.lambda" as P"#)?;
+        assert!(calc.defs.iter().any(|def| def.name == "P.Succ"));
+        calc.init("Not True")?;
+        calc.replace_def_alpha(0, "Not", 1)?;
+        assert_eq!(calc.steps[1].raw_exp, lambda!((b. b False True) True));
+        Ok(())
+    }
 }
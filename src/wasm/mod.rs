@@ -1,6 +1,6 @@
 #![warn(missing_docs)]
 //! <span class="feat-badge" style="color: chocolate; font-weight: bold; background: blanchedalmond; padding: 0 5px; border-radius: 5px; display: inline-block;">feature: wasm</span> interprete lambda expressions in browser
-use crate::parser;
+use crate::{parser, Error};
 use exp::JsExp;
 use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
 
@@ -28,3 +28,16 @@ pub fn parse_def(lambda: &str) -> Result<JsValue, String> {
     let jsexp = JsExp::from_exp(&exp);
     serde_wasm_bindgen::to_value(&(name, jsexp, tokens)).map_err(|e| e.to_string())
 }
+
+/// Check multiline definitions, locating every bad line and suspicious definition
+///
+/// return ```(Vec<ParseError>, Vec<Warning>)```, whose errors are empty if `content` parses
+#[wasm_bindgen]
+pub fn check_defs(content: &str) -> Result<JsValue, String> {
+    let (errors, warnings) = match parser::parse_module(content) {
+        Ok(module) => (vec![], module.warnings),
+        Err(Error::ParseError(errors)) => (errors, vec![]),
+        Err(e) => return Err(e.to_string()),
+    };
+    serde_wasm_bindgen::to_value(&(errors, warnings)).map_err(|e| e.to_string())
+}